@@ -1,4 +1,18 @@
-use tauri::Manager;
+use crate::commands::batch::{self, BatchAction};
+use crate::commands::{process_adopt, process_guard, settings};
+use crate::models::{
+    AutoStartEntry, AutoStartStore, BatchTunnelResult, BatchTunnelTarget, FrpcProcesses,
+    LaunchAutoStartState, TunnelKey, TunnelKind,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+const MAX_START_DELAY_MS: u64 = 10 * 60 * 1000;
+
+// 本次启动是否由后端自动启动隧道，以及完成后的结果
+static LAUNCH_AUTO_START: AtomicBool = AtomicBool::new(false);
+static LAUNCH_RESULTS: Mutex<Option<Vec<BatchTunnelResult>>> = Mutex::new(None);
 
 #[tauri::command]
 pub async fn is_autostart_enabled(
@@ -25,101 +39,201 @@ pub async fn set_autostart(
     }
 }
 
+fn sorted_entries(mut entries: Vec<AutoStartEntry>) -> Vec<AutoStartEntry> {
+    entries.sort_by_key(|entry| entry.order);
+    entries
+}
+
 /// 获取指定隧道的自动启动设置
 #[tauri::command]
 pub async fn get_tunnel_auto_start(
-    tunnel_type: String, // "api" or "custom"
-    tunnel_id: String,   // String ID (can be number as string for api, or uuid for custom)
+    tunnel_type: TunnelKind,
+    tunnel_id: String, // String ID (can be number as string for api, or uuid for custom)
     app: tauri::AppHandle,
 ) -> Result<bool, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
-    
-    let config_path = app_data_dir.join("tunnel_auto_start.json");
-    
-    if !config_path.exists() {
-        return Ok(false);
-    }
-    
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("读取配置文件失败: {}", e))?;
-    
-    let config: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("解析配置文件失败: {}", e))?;
-    
-    let key = format!("{}_{}", tunnel_type, tunnel_id);
-    Ok(config.get(&key).and_then(|v| v.as_bool()).unwrap_or(false))
+    let store = settings::load::<AutoStartStore>(&app)?;
+    Ok(store
+        .entries
+        .iter()
+        .any(|entry| entry.kind == tunnel_type && entry.tunnel_id == tunnel_id))
 }
 
 /// 设置指定隧道的自动启动
 #[tauri::command]
 pub async fn set_tunnel_auto_start(
-    tunnel_type: String, // "api" or "custom"
-    tunnel_id: String,   // String ID (can be number as string for api, or uuid for custom)
+    tunnel_type: TunnelKind,
+    tunnel_id: String, // String ID (can be number as string for api, or uuid for custom)
     enabled: bool,
     app: tauri::AppHandle,
 ) -> Result<(), String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
-    
-    std::fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("创建应用数据目录失败: {}", e))?;
-    
-    let config_path = app_data_dir.join("tunnel_auto_start.json");
-    
-    let mut config: serde_json::Map<String, serde_json::Value> = if config_path.exists() {
-        let content = std::fs::read_to_string(&config_path)
-            .map_err(|e| format!("读取配置文件失败: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("解析配置文件失败: {}", e))?
-    } else {
-        serde_json::Map::new()
-    };
-    
-    let key = format!("{}_{}", tunnel_type, tunnel_id);
-    config.insert(key, serde_json::Value::Bool(enabled));
-    
-    std::fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
-    
-    Ok(())
+    settings::update(&app, |store: &mut AutoStartStore| {
+        let exists = store
+            .entries
+            .iter()
+            .any(|entry| entry.kind == tunnel_type && entry.tunnel_id == tunnel_id);
+
+        if enabled && !exists {
+            // 新加入的隧道排在最后
+            let order = store
+                .entries
+                .iter()
+                .map(|entry| entry.order + 1)
+                .max()
+                .unwrap_or(0);
+            store.entries.push(AutoStartEntry {
+                kind: tunnel_type,
+                tunnel_id,
+                delay_ms: 0,
+                order,
+            });
+        } else if !enabled {
+            store
+                .entries
+                .retain(|entry| !(entry.kind == tunnel_type && entry.tunnel_id == tunnel_id));
+        }
+        Ok(())
+    })
 }
 
-/// 获取所有自动启动的隧道列表
+/// 设置自动启动隧道启动前的等待时间
 #[tauri::command]
-pub async fn get_auto_start_tunnels(
+pub async fn set_tunnel_auto_start_delay(
+    tunnel_type: TunnelKind,
+    tunnel_id: String,
+    delay_ms: u64,
     app: tauri::AppHandle,
-) -> Result<Vec<(String, String)>, String> {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
-    
-    let config_path = app_data_dir.join("tunnel_auto_start.json");
-    
-    if !config_path.exists() {
-        return Ok(vec![]);
+) -> Result<(), String> {
+    if delay_ms > MAX_START_DELAY_MS {
+        return Err(format!("启动延迟不能超过 {} 秒", MAX_START_DELAY_MS / 1000));
     }
-    
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("读取配置文件失败: {}", e))?;
-    
-    let config: serde_json::Map<String, serde_json::Value> = serde_json::from_str(&content)
-        .map_err(|e| format!("解析配置文件失败: {}", e))?;
-    
-    let mut result = vec![];
-    for (key, value) in config {
-        if let Some(true) = value.as_bool() {
-            // 解析 key 格式: "api_123" or "custom_uuid"
-            if let Some((tunnel_type, id_str)) = key.rsplit_once('_') {
-                result.push((tunnel_type.to_string(), id_str.to_string()));
+
+    settings::update(&app, |store: &mut AutoStartStore| {
+        let entry = store
+            .entries
+            .iter_mut()
+            .find(|entry| entry.kind == tunnel_type && entry.tunnel_id == tunnel_id)
+            .ok_or_else(|| "该隧道未启用自动启动".to_string())?;
+        entry.delay_ms = delay_ms;
+        Ok(())
+    })
+}
+
+/// 按传入的顺序重新排列自动启动隧道，未列出的隧道保持原有相对顺序排在后面
+#[tauri::command]
+pub async fn set_auto_start_order(
+    tunnels: Vec<(TunnelKind, String)>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    settings::update(&app, |store: &mut AutoStartStore| {
+        let mut remaining = sorted_entries(std::mem::take(&mut store.entries));
+        let mut entries = Vec::with_capacity(remaining.len());
+
+        for (kind, tunnel_id) in tunnels {
+            if let Some(index) = remaining
+                .iter()
+                .position(|entry| entry.kind == kind && entry.tunnel_id == tunnel_id)
+            {
+                entries.push(remaining.remove(index));
             }
         }
+        entries.extend(remaining);
+
+        for (index, entry) in entries.iter_mut().enumerate() {
+            entry.order = index as u32;
+        }
+        store.entries = entries;
+        Ok(())
+    })
+}
+
+/// 获取所有自动启动的隧道列表，按启动顺序排列
+#[tauri::command]
+pub async fn get_auto_start_tunnels(app: tauri::AppHandle) -> Result<Vec<AutoStartEntry>, String> {
+    Ok(sorted_entries(
+        settings::load::<AutoStartStore>(&app)?.entries,
+    ))
+}
+
+fn skip_reason(app_handle: &tauri::AppHandle, target: &BatchTunnelTarget) -> Option<&'static str> {
+    let tunnel_key = TunnelKey::new(target.kind, &target.tunnel_id).ok()?;
+    if process_guard::is_restart_pending(app_handle, &tunnel_key) {
+        return Some("已由守护进程安排启动");
+    }
+    if process_adopt::is_running(&app_handle.state::<FrpcProcesses>(), &tunnel_key) {
+        return Some("隧道已在运行中");
+    }
+    None
+}
+
+// 启动软件时在后端直接启动标记为自动启动的隧道，不依赖前端页面
+pub fn auto_start_tunnels_on_launch(app_handle: tauri::AppHandle) {
+    if !crate::get_auto_start_tunnels_setting(&app_handle) {
+        return;
     }
-    
-    Ok(result)
+    LAUNCH_AUTO_START.store(true, Ordering::SeqCst);
+
+    tauri::async_runtime::spawn(async move {
+        let entries = match get_auto_start_tunnels(app_handle.clone()).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("[自动启动] 读取自动启动隧道失败: {}", e);
+                Vec::new()
+            }
+        };
+
+        // 按顺序逐个启动，每个隧道启动前等待其设置的延迟
+        let mut results = Vec::with_capacity(entries.len());
+        for entry in entries {
+            if entry.delay_ms > 0 {
+                let delay = entry.delay_ms.min(MAX_START_DELAY_MS);
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+            }
+
+            let target = BatchTunnelTarget {
+                kind: entry.kind,
+                tunnel_id: entry.tunnel_id,
+                config: None,
+            };
+            // 守护进程已安排重启或已在运行的隧道不再重复启动
+            if let Some(message) = skip_reason(&app_handle, &target) {
+                results.push(BatchTunnelResult {
+                    kind: target.kind,
+                    tunnel_id: target.tunnel_id,
+                    success: true,
+                    message: message.to_string(),
+                });
+                continue;
+            }
+            results.extend(
+                batch::run_batch(&app_handle, BatchAction::Start, vec![target], Some(1)).await,
+            );
+        }
+
+        for result in results.iter().filter(|r| !r.success) {
+            eprintln!(
+                "[自动启动] 隧道 {} 启动失败: {}",
+                result.tunnel_id, result.message
+            );
+        }
+
+        if let Ok(mut launch_results) = LAUNCH_RESULTS.lock() {
+            *launch_results = Some(results.clone());
+        }
+        let _ = app_handle.emit("tunnels-auto-started", results);
+    });
+}
+
+/// 获取启动软件时后端自动启动隧道的状态
+///
+/// 后端负责自动启动时前端只同步运行状态，避免与后端按顺序和延迟启动的过程冲突
+#[tauri::command]
+pub async fn get_launch_auto_start_state() -> Result<LaunchAutoStartState, String> {
+    let results = LAUNCH_RESULTS
+        .lock()
+        .map_err(|e| format!("获取自动启动结果失败: {}", e))?
+        .clone();
+    Ok(LaunchAutoStartState {
+        enabled: LAUNCH_AUTO_START.load(Ordering::SeqCst),
+        results,
+    })
 }
@@ -0,0 +1,219 @@
+use crate::commands::custom_tunnel;
+use crate::commands::{process, tunnel_config_cache};
+use crate::models::{
+    BatchTunnelResult, BatchTunnelTarget, FrpcProcesses, ProcessGuardState, TunnelConfig,
+    TunnelKey, TunnelKind, TunnelType,
+};
+use futures_util::stream::{self, StreamExt};
+use std::collections::HashSet;
+use tauri::Manager;
+
+const DEFAULT_CONCURRENCY: usize = 4;
+const MAX_CONCURRENCY: usize = 16;
+
+#[derive(Clone, Copy)]
+pub enum BatchAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+// 未传入配置时，依次使用守护列表和本地缓存中记录的最近一次启动配置
+fn resolve_api_config(
+    app_handle: &tauri::AppHandle,
+    tunnel_id: i32,
+    config: Option<TunnelConfig>,
+) -> Result<TunnelConfig, String> {
+    if let Some(config) = config {
+        return Ok(config);
+    }
+
+    app_handle
+        .state::<ProcessGuardState>()
+        .guarded_processes
+        .lock()
+        .ok()
+        .and_then(|guarded| match guarded.get(&TunnelKey::Api(tunnel_id)) {
+            Some(info) => match &info.tunnel_type {
+                TunnelType::Api { config } => Some(config.clone()),
+                TunnelType::Custom { .. } => None,
+            },
+            None => None,
+        })
+        .or_else(|| tunnel_config_cache::get_cached_api_config(app_handle, tunnel_id))
+        .ok_or_else(|| format!("缺少隧道 {} 的启动配置", tunnel_id))
+        .and_then(|config| {
+            // 缓存中不保存 token，需要由界面传入完整配置启动
+            if config.has_tokens() {
+                Ok(config)
+            } else {
+                Err(format!(
+                    "隧道 {} 的 token 未保存，需要在界面中启动",
+                    tunnel_id
+                ))
+            }
+        })
+}
+
+async fn start_target(
+    app_handle: &tauri::AppHandle,
+    target: BatchTunnelTarget,
+) -> Result<String, String> {
+    match target.kind {
+        TunnelKind::Api => {
+            let tunnel_id = parse_api_id(&target.tunnel_id)?;
+            let config = resolve_api_config(app_handle, tunnel_id, target.config)?;
+            process::start_frpc(
+                app_handle.clone(),
+                config,
+                app_handle.state::<FrpcProcesses>(),
+                app_handle.state::<ProcessGuardState>(),
+            )
+            .await
+        }
+        TunnelKind::Custom => {
+            custom_tunnel::start_custom_tunnel(
+                app_handle.clone(),
+                target.tunnel_id,
+                app_handle.state::<FrpcProcesses>(),
+                app_handle.state::<ProcessGuardState>(),
+            )
+            .await
+        }
+    }
+}
+
+async fn stop_target(
+    app_handle: &tauri::AppHandle,
+    target: &BatchTunnelTarget,
+) -> Result<String, String> {
+    match target.kind {
+        TunnelKind::Api => {
+            process::stop_frpc(
+                app_handle.clone(),
+                parse_api_id(&target.tunnel_id)?,
+                app_handle.state::<ProcessGuardState>(),
+            )
+            .await
+        }
+        TunnelKind::Custom => {
+            custom_tunnel::stop_custom_tunnel(
+                app_handle.clone(),
+                target.tunnel_id.clone(),
+                app_handle.state::<ProcessGuardState>(),
+            )
+            .await
+        }
+    }
+}
+
+async fn restart_target(
+    app_handle: &tauri::AppHandle,
+    mut target: BatchTunnelTarget,
+) -> Result<String, String> {
+    // 停止前取出守护列表中的配置，停止后守护记录会被移除
+    if target.kind == TunnelKind::Api && target.config.is_none() {
+        let tunnel_id = parse_api_id(&target.tunnel_id)?;
+        target.config = resolve_api_config(app_handle, tunnel_id, None).ok();
+    }
+
+    let _ = stop_target(app_handle, &target).await;
+    start_target(app_handle, target).await
+}
+
+fn parse_api_id(tunnel_id: &str) -> Result<i32, String> {
+    tunnel_id
+        .parse()
+        .map_err(|_| format!("无效的隧道 ID: {}", tunnel_id))
+}
+
+async fn run_target(
+    app_handle: tauri::AppHandle,
+    action: BatchAction,
+    target: BatchTunnelTarget,
+) -> BatchTunnelResult {
+    let kind = target.kind;
+    let tunnel_id = target.tunnel_id.clone();
+
+    let result = match action {
+        BatchAction::Start => start_target(&app_handle, target).await,
+        BatchAction::Stop => stop_target(&app_handle, &target).await,
+        BatchAction::Restart => restart_target(&app_handle, target).await,
+    };
+
+    let (success, message) = match result {
+        Ok(message) => (true, message),
+        Err(message) => (false, message),
+    };
+
+    BatchTunnelResult {
+        kind,
+        tunnel_id,
+        success,
+        message,
+    }
+}
+
+// 以有限并发批量执行，结果顺序与传入顺序一致
+pub async fn run_batch(
+    app_handle: &tauri::AppHandle,
+    action: BatchAction,
+    targets: Vec<BatchTunnelTarget>,
+    concurrency: Option<usize>,
+) -> Vec<BatchTunnelResult> {
+    let concurrency = concurrency
+        .unwrap_or(DEFAULT_CONCURRENCY)
+        .clamp(1, MAX_CONCURRENCY);
+
+    // 同一隧道只执行一次，避免并发启动同一隧道
+    let mut seen = HashSet::new();
+    let targets: Vec<_> = targets
+        .into_iter()
+        .filter(|target| seen.insert((target.kind, target.tunnel_id.clone())))
+        .collect();
+
+    // 启动流程中有同步的文件读写和子进程调用，每个隧道放到单独的任务中执行才能真正并发
+    stream::iter(targets)
+        .map(|target| {
+            let (kind, tunnel_id) = (target.kind, target.tunnel_id.clone());
+            let task = tauri::async_runtime::spawn(run_target(app_handle.clone(), action, target));
+            async move {
+                task.await.unwrap_or_else(|e| BatchTunnelResult {
+                    kind,
+                    tunnel_id,
+                    success: false,
+                    message: format!("执行隧道操作失败: {}", e),
+                })
+            }
+        })
+        .buffered(concurrency)
+        .collect()
+        .await
+}
+
+#[tauri::command]
+pub async fn start_tunnels(
+    app_handle: tauri::AppHandle,
+    targets: Vec<BatchTunnelTarget>,
+    concurrency: Option<usize>,
+) -> Result<Vec<BatchTunnelResult>, String> {
+    Ok(run_batch(&app_handle, BatchAction::Start, targets, concurrency).await)
+}
+
+#[tauri::command]
+pub async fn stop_tunnels(
+    app_handle: tauri::AppHandle,
+    targets: Vec<BatchTunnelTarget>,
+    concurrency: Option<usize>,
+) -> Result<Vec<BatchTunnelResult>, String> {
+    Ok(run_batch(&app_handle, BatchAction::Stop, targets, concurrency).await)
+}
+
+#[tauri::command]
+pub async fn restart_tunnels(
+    app_handle: tauri::AppHandle,
+    targets: Vec<BatchTunnelTarget>,
+    concurrency: Option<usize>,
+) -> Result<Vec<BatchTunnelResult>, String> {
+    Ok(run_batch(&app_handle, BatchAction::Restart, targets, concurrency).await)
+}
@@ -0,0 +1,47 @@
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+pub const KEY_LEN: usize = 32;
+pub const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+pub fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+// 使用 Argon2id 从口令派生密钥
+pub fn derive_key(secret: &[u8], salt: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    let mut key = [0u8; KEY_LEN];
+    argon2::Argon2::default()
+        .hash_password_into(secret, salt, &mut key)
+        .map_err(|e| format!("派生密钥失败: {}", e))?;
+    Ok(key)
+}
+
+// 加密结果为 nonce + 密文
+pub fn seal(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "加密失败".to_string())?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+pub fn open(key: &[u8; KEY_LEN], sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("密文长度无效".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "解密失败，密钥错误或数据已损坏".to_string())
+}
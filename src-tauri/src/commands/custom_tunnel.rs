@@ -1,19 +1,24 @@
-use crate::models::{FrpcProcesses, LogMessage, ProcessGuardState};
+use crate::commands::frpc_config::{self, ConfigFormat};
+use crate::commands::{
+    frpc_output, frpc_version, process_adopt, process_exit, process_stop, settings, token_vault,
+    tunnel_status,
+};
+use crate::models::{
+    FrpcProcesses, LogMessage, ProcessGuardState, StopMethod, TunnelKey, TunnelStatus,
+};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command as StdCommand, Stdio};
-use std::thread;
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
 use tauri::{Emitter, Manager, State};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
 
-const CUSTOM_TUNNEL_PREFIX: &str = "custom_";
 const CONFIG_FILE_PREFIX: &str = "z_";
-const CONFIG_FILE_EXT: &str = ".ini";
-const TUNNELS_LIST_FILE: &str = "custom_tunnels.json";
+// 配置文件中 token 的占位标记，原值保存在保险库中
+const SECRET_MARKER_PREFIX: &str = "__CHMLFRP_SECRET_";
+const SECRET_ENV_PREFIX: &str = "CHMLFRP_SECRET_";
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CustomTunnel {
@@ -30,8 +35,6 @@ pub struct CustomTunnel {
     pub local_port: Option<u16>,
     pub remote_port: Option<u16>,
     pub created_at: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub hashed_id: Option<i32>,
 }
 
 fn get_app_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
@@ -41,70 +44,188 @@ fn get_app_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
         .map_err(|e| format!("获取应用目录失败: {}", e))
 }
 
-fn get_custom_tunnel_hash(tunnel_id: &str) -> i32 {
-    string_to_i32(&format!("{}{}", CUSTOM_TUNNEL_PREFIX, tunnel_id))
+// 隧道名称同时作为 id 和配置文件名的一部分
+pub fn is_valid_tunnel_id(tunnel_id: &str) -> bool {
+    !tunnel_id.is_empty()
+        && tunnel_id
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
 }
 
-fn get_config_file_name(tunnel_id: &str) -> String {
-    format!("{}{}{}", CONFIG_FILE_PREFIX, tunnel_id, CONFIG_FILE_EXT)
+pub fn get_config_file_name(tunnel_id: &str, format: ConfigFormat) -> String {
+    format!("{}{}.{}", CONFIG_FILE_PREFIX, tunnel_id, format.extension())
 }
 
-fn get_frpc_path(app_dir: &PathBuf) -> PathBuf {
-    if cfg!(target_os = "windows") {
-        app_dir.join("frpc.exe")
-    } else {
-        app_dir.join("frpc")
+fn find_config_file(app_dir: &Path, tunnel_id: &str) -> Option<String> {
+    ConfigFormat::ALL
+        .iter()
+        .map(|format| get_config_file_name(tunnel_id, *format))
+        .find(|file_name| app_dir.join(file_name).exists())
+}
+
+pub fn remove_config_files(
+    app_dir: &Path,
+    tunnel_id: &str,
+    keep: Option<ConfigFormat>,
+) -> Result<(), String> {
+    for format in ConfigFormat::ALL {
+        if Some(format) == keep {
+            continue;
+        }
+        let config_file = app_dir.join(get_config_file_name(tunnel_id, format));
+        if config_file.exists() {
+            fs::remove_file(&config_file).map_err(|e| format!("删除配置文件失败: {}", e))?;
+        }
     }
+    Ok(())
 }
 
-fn spawn_log_reader(
-    app_handle: tauri::AppHandle,
-    reader: Box<dyn BufRead + Send>,
-    tunnel_id_hash: i32,
-    tunnel_id: String,
-    is_stderr: bool,
-) {
-    let thread_name = format!(
-        "custom-frpc-{}-{}",
-        if is_stderr { "stderr" } else { "stdout" },
-        tunnel_id
-    );
+fn secret_marker(index: usize) -> String {
+    format!("{}{}__", SECRET_MARKER_PREFIX, index)
+}
 
-    thread::Builder::new()
-        .name(thread_name)
-        .spawn(move || {
-            for line in reader.lines().flatten() {
-                let clean_line = strip_ansi_escapes::strip_str(&line);
-                let timestamp = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
-
-                let guard_state = app_handle.state::<ProcessGuardState>();
-                let _ = tauri::async_runtime::block_on(async {
-                    crate::commands::process_guard::check_log_and_stop_guard(
-                        app_handle.clone(),
-                        tunnel_id_hash,
-                        clean_line.clone(),
-                        guard_state,
-                    )
-                    .await
-                });
-
-                let message = if is_stderr {
-                    format!("[ERR] {}", clean_line)
-                } else {
-                    clean_line
-                };
-
-                let _ = app_handle.emit(
-                    "frpc-log",
-                    LogMessage {
-                        tunnel_id: tunnel_id_hash,
-                        message,
-                        timestamp,
-                    },
-                );
-            }
+fn secret_env_name(index: usize) -> String {
+    format!("{}{}", SECRET_ENV_PREFIX, index)
+}
+
+// 将配置中的 token 替换为占位标记，原值存入保险库
+fn seal_config_secrets(
+    app_handle: &tauri::AppHandle,
+    tunnel_id: &str,
+    content: &str,
+) -> Result<String, String> {
+    // 内容中残留的旧占位标记先还原，再统一重新编号
+    let content = materialize_config_content(app_handle, tunnel_id, content)?;
+    let secrets = extract_config_secrets(&content, detect_config_format(&content));
+
+    let entry = token_vault::custom_entry(tunnel_id);
+    if secrets.is_empty() {
+        token_vault::remove_secrets(app_handle, &entry);
+        return Ok(content);
+    }
+
+    let sealed = replace_secret_values(&content, &secrets, secret_marker)?;
+    token_vault::store_secrets(app_handle, &entry, secrets)?;
+    Ok(sealed)
+}
+
+// 值前面紧接 user 或 token 键及分隔符时，才视为需要替换的 token
+fn is_secret_value_at(content: &str, start: usize, end: usize) -> bool {
+    let value_ends = content[end..].chars().next().map_or(true, |c| {
+        c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '}' | ']' | '#' | ';')
+    });
+    let before = content[..start].trim_end_matches(['"', '\'']).trim_end();
+    let Some(key) = before.strip_suffix(['=', ':']) else {
+        return false;
+    };
+    let key = key.trim_end().trim_end_matches(['"', '\'']);
+    value_ends
+        && ["user", "token"].iter().any(|name| {
+            key.strip_suffix(name)
+                .is_some_and(|rest| !rest.ends_with(|c: char| c.is_alphanumeric() || c == '_'))
         })
-        .ok();
+}
+
+// 按位置替换 user 和 token 的值，其他字段中相同的文本保持不变
+fn replace_secret_values(
+    content: &str,
+    secrets: &[String],
+    replacement: impl Fn(usize) -> String,
+) -> Result<String, String> {
+    let mut spans = Vec::new();
+    for (index, secret) in secrets.iter().enumerate() {
+        let found = spans.len();
+        spans.extend(
+            content
+                .match_indices(secret.as_str())
+                .map(|(start, _)| (start, start + secret.len(), index))
+                .filter(|&(start, end, _)| is_secret_value_at(content, start, end)),
+        );
+        if spans.len() == found {
+            return Err("无法定位配置中的 token，请检查配置格式".to_string());
+        }
+    }
+
+    // 同一位置匹配到多个值时保留较长的一个
+    spans.sort_by_key(|&(start, end, _)| (start, std::cmp::Reverse(end)));
+    spans.dedup_by(|next, prev| next.0 < prev.1);
+
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end, index) in spans {
+        result.push_str(&content[last..start]);
+        result.push_str(&replacement(index));
+        last = end;
+    }
+    result.push_str(&content[last..]);
+    Ok(result)
+}
+
+fn replace_secret_markers(
+    content: &str,
+    secrets: &[String],
+    replacement: impl Fn(usize) -> String,
+) -> Result<String, String> {
+    let mut result = content.to_string();
+    for index in 0..secrets.len() {
+        result = result.replace(&secret_marker(index), &replacement(index));
+    }
+    if result.contains(SECRET_MARKER_PREFIX) {
+        return Err("配置中的 token 无法从保险库中读取，请重新编辑该隧道的配置".to_string());
+    }
+    Ok(result)
+}
+
+/// 还原占位标记，得到用户原始的配置内容
+pub fn materialize_config_content(
+    app_handle: &tauri::AppHandle,
+    tunnel_id: &str,
+    content: &str,
+) -> Result<String, String> {
+    if !content.contains(SECRET_MARKER_PREFIX) {
+        return Ok(content.to_string());
+    }
+    let secrets = token_vault::load_secrets(app_handle, &token_vault::custom_entry(tunnel_id));
+    replace_secret_markers(content, &secrets, |index| secrets[index].clone())
+}
+
+/// 写入自定义隧道配置文件，token 不以明文落盘
+pub fn write_config_file(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    tunnel_id: &str,
+    config_file_name: &str,
+    content: &str,
+) -> Result<(), String> {
+    let sealed = seal_config_secrets(app_handle, tunnel_id, content)?;
+    settings::write_atomic(&app_dir.join(config_file_name), sealed.as_bytes(), true)
+        .map_err(|e| format!("写入配置文件失败: {}", e))
+}
+
+// 旧版本以明文保存的配置，启动时将 token 移入保险库
+pub fn seal_plaintext_configs(app_handle: &tauri::AppHandle) {
+    let Ok(app_dir) = get_app_dir(app_handle) else {
+        return;
+    };
+    let Ok(tunnels) = settings::load::<Vec<CustomTunnel>>(app_handle) else {
+        return;
+    };
+
+    for tunnel in tunnels {
+        let Some(config_file) = find_config_file(&app_dir, &tunnel.id) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(app_dir.join(&config_file)) else {
+            continue;
+        };
+        if extract_config_secrets(&content, detect_config_format(&content)).is_empty() {
+            continue;
+        }
+        if let Err(e) = write_config_file(app_handle, &app_dir, &tunnel.id, &config_file, &content)
+        {
+            eprintln!("[保险库] 迁移自定义隧道 {} 的 token 失败: {}", tunnel.id, e);
+        }
+    }
 }
 
 #[tauri::command]
@@ -113,38 +234,34 @@ pub async fn save_custom_tunnel(
     _tunnel_name: String,
     config_content: String,
 ) -> Result<Vec<CustomTunnel>, String> {
-    let split = split_ini_config(&config_content)?;
+    let format = detect_config_format(&config_content);
+    let split = split_tunnel_config(&config_content, format)?;
 
-    if split.tunnels.is_empty() {
+    if split.is_empty() {
         return Err("配置文件中未找到隧道名称".to_string());
     }
 
     let app_dir = get_app_dir(&app_handle)?;
     fs::create_dir_all(&app_dir).map_err(|e| format!("创建目录失败: {}", e))?;
 
-    let mut created = Vec::with_capacity(split.tunnels.len());
+    let mut created = Vec::with_capacity(split.len());
 
-    for (tunnel_name, tunnel_block) in split.tunnels {
-        if !tunnel_name
-            .chars()
-            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-        {
+    for (tunnel_name, single_config) in split {
+        if !is_valid_tunnel_id(&tunnel_name) {
             return Err("配置文件中的隧道名称只能包含字母、数字、下划线和连字符".to_string());
         }
 
-        let single_ini = if split.common.trim().is_empty() {
-            tunnel_block
-        } else {
-            format!("{}\n\n{}", split.common, tunnel_block)
-        };
-
-        let parsed_info = parse_ini_config(&single_ini)?;
-
-        let config_file_name = get_config_file_name(&tunnel_name);
-        let config_file_path = app_dir.join(&config_file_name);
+        let parsed_info = parse_tunnel_config(&single_config, format)?;
 
-        fs::write(&config_file_path, &single_ini)
-            .map_err(|e| format!("写入配置文件失败: {}", e))?;
+        let config_file_name = get_config_file_name(&tunnel_name, format);
+        write_config_file(
+            &app_handle,
+            &app_dir,
+            &tunnel_name,
+            &config_file_name,
+            &single_config,
+        )?;
+        remove_config_files(&app_dir, &tunnel_name, Some(format))?;
 
         let custom_tunnel = CustomTunnel {
             id: tunnel_name.clone(),
@@ -160,7 +277,6 @@ pub async fn save_custom_tunnel(
             local_port: parsed_info.local_port,
             remote_port: parsed_info.remote_port,
             created_at: chrono::Local::now().to_rfc3339(),
-            hashed_id: Some(get_custom_tunnel_hash(&tunnel_name)),
         };
 
         save_custom_tunnel_list(&app_handle, &custom_tunnel)?;
@@ -173,24 +289,15 @@ pub async fn save_custom_tunnel(
 #[tauri::command]
 pub async fn get_custom_tunnels(app_handle: tauri::AppHandle) -> Result<Vec<CustomTunnel>, String> {
     let app_dir = get_app_dir(&app_handle)?;
-    let list_file = app_dir.join(TUNNELS_LIST_FILE);
-
-    if !list_file.exists() {
-        return Ok(Vec::new());
-    }
-
-    let content =
-        fs::read_to_string(&list_file).map_err(|e| format!("读取自定义隧道列表失败: {}", e))?;
-
-    let tunnels: Vec<CustomTunnel> =
-        serde_json::from_str(&content).map_err(|e| format!("解析自定义隧道列表失败: {}", e))?;
+    let tunnels: Vec<CustomTunnel> = settings::load(&app_handle)
+        .map_err(|e| format!("读取自定义隧道列表失败: {}", e))?;
 
     let updated = tunnels
         .into_iter()
         .map(|mut t| {
             let config_path = app_dir.join(&t.config_file);
             if let Ok(cfg) = fs::read_to_string(&config_path) {
-                if let Ok(parsed) = parse_ini_config(&cfg) {
+                if let Ok(parsed) = parse_tunnel_config(&cfg, detect_config_format(&cfg)) {
                     t.server_addr = parsed.server_addr.or(t.server_addr);
                     t.server_port = parsed.server_port.or(t.server_port);
                     if !parsed.tunnel_names.is_empty() {
@@ -204,7 +311,6 @@ pub async fn get_custom_tunnels(app_handle: tauri::AppHandle) -> Result<Vec<Cust
                     t.remote_port = parsed.remote_port.or(t.remote_port);
                 }
             }
-            t.hashed_id = Some(get_custom_tunnel_hash(&t.id));
             t
         })
         .collect();
@@ -255,6 +361,29 @@ fn split_ini_config(content: &str) -> Result<IniSplitResult, String> {
     Ok(IniSplitResult { common, tunnels })
 }
 
+fn split_tunnel_config(
+    content: &str,
+    format: ConfigFormat,
+) -> Result<Vec<(String, String)>, String> {
+    if format != ConfigFormat::Ini {
+        return split_structured_config(content, format);
+    }
+
+    let split = split_ini_config(content)?;
+    Ok(split
+        .tunnels
+        .into_iter()
+        .map(|(name, block)| {
+            let single_ini = if split.common.trim().is_empty() {
+                block
+            } else {
+                format!("{}\n\n{}", split.common, block)
+            };
+            (name, single_ini)
+        })
+        .collect())
+}
+
 fn parse_section_header(line: &str) -> Option<String> {
     if line.starts_with('[') && line.ends_with(']') {
         Some(line[1..line.len() - 1].trim().to_string())
@@ -274,13 +403,11 @@ pub async fn get_custom_tunnel_config(
     tunnel_id: String,
 ) -> Result<String, String> {
     let app_dir = get_app_dir(&app_handle)?;
-    let config_file_path = app_dir.join(get_config_file_name(&tunnel_id));
-
-    if !config_file_path.exists() {
-        return Err("配置文件不存在".to_string());
-    }
+    let config_file = find_config_file(&app_dir, &tunnel_id).ok_or("配置文件不存在")?;
 
-    fs::read_to_string(&config_file_path).map_err(|e| format!("读取配置文件失败: {}", e))
+    let content = fs::read_to_string(app_dir.join(config_file))
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    materialize_config_content(&app_handle, &tunnel_id, &content)
 }
 
 #[tauri::command]
@@ -290,23 +417,21 @@ pub async fn update_custom_tunnel(
     config_content: String,
 ) -> Result<CustomTunnel, String> {
     let app_dir = get_app_dir(&app_handle)?;
-    let parsed_info = parse_ini_config(&config_content)?;
-
-    let config_file_name = get_config_file_name(&tunnel_id);
-    let config_file_path = app_dir.join(&config_file_name);
-
-    fs::write(&config_file_path, &config_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
-
-    let list_file = app_dir.join(TUNNELS_LIST_FILE);
-    let existing_tunnels: Vec<CustomTunnel> = if list_file.exists() {
-        let content = fs::read_to_string(&list_file)
-            .map_err(|e| format!("读取自定义隧道列表失败: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("解析自定义隧道列表失败: {}", e))?
-    } else {
-        Vec::new()
-    };
+    let format = detect_config_format(&config_content);
+    let parsed_info = parse_tunnel_config(&config_content, format)?;
+
+    let config_file_name = get_config_file_name(&tunnel_id, format);
+    write_config_file(
+        &app_handle,
+        &app_dir,
+        &tunnel_id,
+        &config_file_name,
+        &config_content,
+    )?;
+    remove_config_files(&app_dir, &tunnel_id, Some(format))?;
+
+    let existing_tunnels: Vec<CustomTunnel> = settings::load(&app_handle)
+        .map_err(|e| format!("读取自定义隧道列表失败: {}", e))?;
 
     let created_at = existing_tunnels
         .iter()
@@ -328,7 +453,6 @@ pub async fn update_custom_tunnel(
         local_port: parsed_info.local_port,
         remote_port: parsed_info.remote_port,
         created_at,
-        hashed_id: Some(get_custom_tunnel_hash(&tunnel_id)),
     };
 
     save_custom_tunnel_list(&app_handle, &updated_tunnel)?;
@@ -339,44 +463,23 @@ pub async fn update_custom_tunnel(
 pub async fn delete_custom_tunnel(
     app_handle: tauri::AppHandle,
     tunnel_id: String,
-    processes: State<'_, FrpcProcesses>,
 ) -> Result<(), String> {
-    let tunnel_id_hash = get_custom_tunnel_hash(&tunnel_id);
+    let tunnel_key = TunnelKey::Custom(tunnel_id.clone());
 
-    {
-        let mut procs = processes
-            .processes
-            .lock()
-            .map_err(|e| format!("获取进程锁失败: {}", e))?;
-
-        if let Some(mut child) = procs.remove(&tunnel_id_hash) {
-            let _ = child.kill();
-            let _ = child.wait();
-        }
-    }
+    let _ = process_stop::stop_tunnel_process(&app_handle, &tunnel_key).await;
 
     let app_dir = get_app_dir(&app_handle)?;
 
-    let config_file = app_dir.join(get_config_file_name(&tunnel_id));
-    if config_file.exists() {
-        fs::remove_file(&config_file).map_err(|e| format!("删除配置文件失败: {}", e))?;
-    }
-
-    let list_file = app_dir.join(TUNNELS_LIST_FILE);
-    if list_file.exists() {
-        let content =
-            fs::read_to_string(&list_file).map_err(|e| format!("读取自定义隧道列表失败: {}", e))?;
-
-        let mut tunnels: Vec<CustomTunnel> =
-            serde_json::from_str(&content).map_err(|e| format!("解析自定义隧道列表失败: {}", e))?;
+    remove_config_files(&app_dir, &tunnel_id, None)?;
+    frpc_config::remove_generated_configs(&app_dir, &tunnel_key);
+    token_vault::remove_secrets(&app_handle, &token_vault::custom_entry(&tunnel_id));
+    frpc_version::remove_pin(&app_handle, &tunnel_key);
 
+    settings::update(&app_handle, |tunnels: &mut Vec<CustomTunnel>| {
         tunnels.retain(|t| t.id != tunnel_id);
-
-        let content = serde_json::to_string_pretty(&tunnels)
-            .map_err(|e| format!("序列化自定义隧道列表失败: {}", e))?;
-
-        fs::write(&list_file, content).map_err(|e| format!("保存自定义隧道列表失败: {}", e))?;
-    }
+        Ok(())
+    })
+    .map_err(|e| format!("保存自定义隧道列表失败: {}", e))?;
 
     Ok(())
 }
@@ -388,24 +491,12 @@ pub async fn start_custom_tunnel(
     processes: State<'_, FrpcProcesses>,
     guard_state: State<'_, ProcessGuardState>,
 ) -> Result<String, String> {
-    let tunnel_id_hash = get_custom_tunnel_hash(&tunnel_id);
+    let tunnel_key = TunnelKey::Custom(tunnel_id.clone());
 
-    {
-        let procs = processes
-            .processes
-            .lock()
-            .map_err(|e| format!("获取进程锁失败: {}", e))?;
-        if procs.contains_key(&tunnel_id_hash) {
-            return Err("该隧道已在运行中".to_string());
-        }
-    }
+    let reservation = process_adopt::reserve_start(&processes, &tunnel_key)?;
 
     let app_dir = get_app_dir(&app_handle)?;
-    let frpc_path = get_frpc_path(&app_dir);
-
-    if !frpc_path.exists() {
-        return Err("frpc 未找到，请先下载".to_string());
-    }
+    let frpc_path = frpc_version::resolve_frpc_path(&app_handle, &app_dir, &tunnel_key)?;
 
     #[cfg(unix)]
     {
@@ -418,34 +509,48 @@ pub async fn start_custom_tunnel(
         }
     }
 
-    let config_file = get_config_file_name(&tunnel_id);
-    let config_path = app_dir.join(&config_file);
-
-    if !config_path.exists() {
-        return Err("配置文件不存在".to_string());
-    }
+    let config_file = find_config_file(&app_dir, &tunnel_id).ok_or("配置文件不存在")?;
+    let content = fs::read_to_string(app_dir.join(&config_file))
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let content = materialize_config_content(&app_handle, &tunnel_id, &content)?;
+    let format = detect_config_format(&content);
+
+    // 运行时副本中的 token 改为引用环境变量，由环境变量传给 frpc
+    let secrets = extract_config_secrets(&content, format);
+    let runtime_content = replace_secret_values(&content, &secrets, |index| {
+        token_vault::env_placeholder(&secret_env_name(index))
+    })?;
+    let runtime_path = app_dir.join(frpc_config::config_file_name(&tunnel_key, format));
+    settings::write_atomic(&runtime_path, runtime_content.as_bytes(), true)
+        .map_err(|e| format!("写入配置文件失败: {}", e))?;
 
+    let (stdout, stderr) = frpc_output::create_output(&app_dir, &tunnel_key)?;
     let mut cmd = StdCommand::new(&frpc_path);
     cmd.current_dir(&app_dir)
         .arg("-c")
-        .arg(&config_file)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        .arg(&runtime_path)
+        .stdout(stdout)
+        .stderr(stderr);
+    for (index, secret) in secrets.iter().enumerate() {
+        cmd.env(secret_env_name(index), secret);
+    }
 
     #[cfg(target_os = "windows")]
     {
         cmd.creation_flags(0x08000000);
     }
 
-    let mut child = cmd.spawn().map_err(|e| format!("启动 frpc 失败: {}", e))?;
+    let child = cmd.spawn().map_err(|e| format!("启动 frpc 失败: {}", e))?;
 
     let pid = child.id();
+    process_adopt::write_pid_file(&app_handle, &tunnel_key, pid, &runtime_path, &frpc_path);
+    tunnel_status::set_status(&app_handle, &tunnel_key, TunnelStatus::Starting);
 
     let timestamp = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
     let _ = app_handle.emit(
         "frpc-log",
         LogMessage {
-            tunnel_id: tunnel_id_hash,
+            tunnel_key: tunnel_key.clone(),
             message: format!(
                 "[I] [ChmlFrpLauncher] 自定义隧道 {} 进程已启动 (PID: {})",
                 tunnel_id, pid
@@ -454,75 +559,53 @@ pub async fn start_custom_tunnel(
         },
     );
 
-    if let Some(stdout) = child.stdout.take() {
-        spawn_log_reader(
-            app_handle.clone(),
-            Box::new(BufReader::new(stdout)),
-            tunnel_id_hash,
-            tunnel_id.clone(),
-            false,
-        );
-    }
-
-    if let Some(stderr) = child.stderr.take() {
-        spawn_log_reader(
-            app_handle.clone(),
-            Box::new(BufReader::new(stderr)),
-            tunnel_id_hash,
-            tunnel_id.clone(),
-            true,
-        );
-    }
+    let output = frpc_output::spawn_log_readers(&app_handle, &app_dir, &tunnel_key, secrets, false);
 
     {
         let mut procs = processes
             .processes
             .lock()
             .map_err(|e| format!("获取进程锁失败: {}", e))?;
-        procs.insert(tunnel_id_hash, child);
+        let child =
+            process_exit::spawn_exit_waiter(app_handle.clone(), tunnel_key.clone(), child, output)?;
+        procs.insert(tunnel_key.clone(), child);
     }
+    drop(reservation);
 
-    let _ = crate::commands::process_guard::add_guarded_custom_tunnel(
-        tunnel_id_hash,
-        tunnel_id.clone(),
-        guard_state,
-    )
-    .await;
+    let _ =
+        crate::commands::process_guard::add_guarded_custom_tunnel(tunnel_id.clone(), guard_state)
+            .await;
 
     Ok(format!("自定义隧道已启动 (PID: {})", pid))
 }
 
 #[tauri::command]
 pub async fn stop_custom_tunnel(
+    app_handle: tauri::AppHandle,
     tunnel_id: String,
-    processes: State<'_, FrpcProcesses>,
     guard_state: State<'_, ProcessGuardState>,
 ) -> Result<String, String> {
-    let tunnel_id_hash = get_custom_tunnel_hash(&tunnel_id);
+    let tunnel_key = TunnelKey::Custom(tunnel_id);
 
-    let _ =
-        crate::commands::process_guard::remove_guarded_process(tunnel_id_hash, guard_state, true)
-            .await;
+    let _ = crate::commands::process_guard::remove_guarded_process(
+        tunnel_key.clone(),
+        guard_state,
+        true,
+    )
+    .await;
 
-    let mut procs = processes
-        .processes
-        .lock()
-        .map_err(|e| format!("获取进程锁失败: {}", e))?;
+    let Some(result) = process_stop::stop_tunnel_process(&app_handle, &tunnel_key).await else {
+        return Err("该隧道未在运行".to_string());
+    };
 
-    if let Some(mut child) = procs.remove(&tunnel_id_hash) {
-        match child.kill() {
-            Ok(_) => {
-                let _ = child.wait();
-                Ok("自定义隧道已停止".to_string())
-            }
-            Err(e) => {
-                let _ = child.wait();
-                Err(format!("停止进程失败: {}", e))
-            }
-        }
-    } else {
-        Err("该隧道未在运行".to_string())
+    if let Ok(app_dir) = get_app_dir(&app_handle) {
+        frpc_config::remove_generated_configs(&app_dir, &tunnel_key);
     }
+
+    result.map(|method| match method {
+        StopMethod::Graceful => "自定义隧道已停止".to_string(),
+        StopMethod::Forced => "自定义隧道已强制停止".to_string(),
+    })
 }
 
 #[tauri::command]
@@ -530,31 +613,14 @@ pub async fn is_custom_tunnel_running(
     tunnel_id: String,
     processes: State<'_, FrpcProcesses>,
 ) -> Result<bool, String> {
-    let tunnel_id_hash = get_custom_tunnel_hash(&tunnel_id);
-
-    let mut procs = processes
-        .processes
-        .lock()
-        .map_err(|e| format!("获取进程锁失败: {}", e))?;
-
-    if let Some(child) = procs.get_mut(&tunnel_id_hash) {
-        match child.try_wait() {
-            Ok(Some(_)) => {
-                procs.remove(&tunnel_id_hash);
-                Ok(false)
-            }
-            Ok(None) => Ok(true),
-            Err(_) => {
-                procs.remove(&tunnel_id_hash);
-                Ok(false)
-            }
-        }
-    } else {
-        Ok(false)
-    }
+    Ok(process_adopt::is_running(
+        &processes,
+        &TunnelKey::Custom(tunnel_id),
+    ))
 }
 
-struct IniParsedInfo {
+#[derive(Default)]
+struct ParsedTunnelInfo {
     server_addr: Option<String>,
     server_port: Option<u16>,
     tunnel_names: Vec<String>,
@@ -566,18 +632,15 @@ struct IniParsedInfo {
     remote_port: Option<u16>,
 }
 
-fn parse_ini_config(content: &str) -> Result<IniParsedInfo, String> {
-    let mut info = IniParsedInfo {
-        server_addr: None,
-        server_port: None,
-        tunnel_names: Vec::new(),
-        tunnel_type: None,
-        custom_domains: None,
-        subdomain: None,
-        local_ip: None,
-        local_port: None,
-        remote_port: None,
-    };
+fn parse_tunnel_config(content: &str, format: ConfigFormat) -> Result<ParsedTunnelInfo, String> {
+    match format {
+        ConfigFormat::Ini => parse_ini_config(content),
+        _ => parse_structured_config(content, format).map(|root| parse_structured_info(&root)),
+    }
+}
+
+fn parse_ini_config(content: &str) -> Result<ParsedTunnelInfo, String> {
+    let mut info = ParsedTunnelInfo::default();
 
     let mut current_section = String::new();
 
@@ -620,40 +683,414 @@ fn parse_ini_config(content: &str) -> Result<IniParsedInfo, String> {
     Ok(info)
 }
 
+// frpc v0.52+ 的 TOML/YAML/JSON 配置，INI 以外的格式统一转换为 JSON 值处理
+fn detect_config_format(content: &str) -> ConfigFormat {
+    if content.trim_start().starts_with('{') {
+        return ConfigFormat::Json;
+    }
+
+    let has_line = |target: &str| content.lines().any(|line| line.trim() == target);
+    if has_line("[[proxies]]") || has_line("[[visitors]]") {
+        return ConfigFormat::Toml;
+    }
+    if has_line("[common]") {
+        return ConfigFormat::Ini;
+    }
+
+    if toml::from_str::<serde_json::Value>(content).is_ok_and(|v| looks_like_frpc_config(&v)) {
+        return ConfigFormat::Toml;
+    }
+    if serde_yaml::from_str::<serde_json::Value>(content).is_ok_and(|v| looks_like_frpc_config(&v))
+    {
+        return ConfigFormat::Yaml;
+    }
+
+    ConfigFormat::Ini
+}
+
+fn looks_like_frpc_config(value: &serde_json::Value) -> bool {
+    ["serverAddr", "serverPort", "proxies", "visitors", "auth"]
+        .iter()
+        .any(|key| value.get(key).is_some())
+}
+
+fn parse_structured_config(
+    content: &str,
+    format: ConfigFormat,
+) -> Result<serde_json::Value, String> {
+    let root: serde_json::Value = match format {
+        ConfigFormat::Toml => {
+            toml::from_str(content).map_err(|e| format!("解析 TOML 配置失败: {}", e))?
+        }
+        ConfigFormat::Yaml => {
+            serde_yaml::from_str(content).map_err(|e| format!("解析 YAML 配置失败: {}", e))?
+        }
+        ConfigFormat::Json => {
+            serde_json::from_str(content).map_err(|e| format!("解析 JSON 配置失败: {}", e))?
+        }
+        ConfigFormat::Ini => return Err("INI 配置不是结构化格式".to_string()),
+    };
+
+    if !root.is_object() {
+        return Err("配置文件格式错误".to_string());
+    }
+
+    Ok(root)
+}
+
+fn serialize_structured_config(
+    root: &serde_json::Value,
+    format: ConfigFormat,
+) -> Result<String, String> {
+    match format {
+        ConfigFormat::Toml => {
+            toml::to_string(root).map_err(|e| format!("序列化 TOML 配置失败: {}", e))
+        }
+        ConfigFormat::Yaml => {
+            serde_yaml::to_string(root).map_err(|e| format!("序列化 YAML 配置失败: {}", e))
+        }
+        ConfigFormat::Json => {
+            serde_json::to_string_pretty(root).map_err(|e| format!("序列化 JSON 配置失败: {}", e))
+        }
+        ConfigFormat::Ini => Err("INI 配置不是结构化格式".to_string()),
+    }
+}
+
+fn split_structured_config(
+    content: &str,
+    format: ConfigFormat,
+) -> Result<Vec<(String, String)>, String> {
+    let root = parse_structured_config(content, format)?;
+    let proxies = root
+        .get("proxies")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    // 只有一个隧道时原样保存，保留用户的注释和排版
+    if let [proxy] = proxies.as_slice() {
+        return Ok(proxy
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(|name| vec![(name.to_string(), content.trim().to_string())])
+            .unwrap_or_default());
+    }
+
+    proxies
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, proxy)| {
+            let name = proxy.get("name")?.as_str()?.to_string();
+            let mut single = root.clone();
+            single["proxies"] = serde_json::Value::Array(vec![proxy]);
+            // visitors 只保留在第一个拆分出的配置中，避免重复监听本地端口
+            if index > 0 {
+                if let Some(obj) = single.as_object_mut() {
+                    obj.remove("visitors");
+                }
+            }
+            Some(serialize_structured_config(&single, format).map(|content| (name, content)))
+        })
+        .collect()
+}
+
+fn parse_structured_info(root: &serde_json::Value) -> ParsedTunnelInfo {
+    let mut info = ParsedTunnelInfo {
+        server_addr: json_str(root, "serverAddr"),
+        server_port: root.get("serverPort").and_then(json_u16),
+        ..Default::default()
+    };
+
+    let proxies = root.get("proxies").and_then(|v| v.as_array());
+    for proxy in proxies.into_iter().flatten() {
+        if let Some(name) = json_str(proxy, "name") {
+            info.tunnel_names.push(name);
+        }
+        if let Some(tunnel_type) = json_str(proxy, "type") {
+            info.tunnel_type = Some(tunnel_type);
+        }
+        if let Some(domains) = proxy.get("customDomains") {
+            info.custom_domains = match domains {
+                serde_json::Value::Array(items) => Some(
+                    items
+                        .iter()
+                        .filter_map(|d| d.as_str())
+                        .collect::<Vec<_>>()
+                        .join(","),
+                ),
+                serde_json::Value::String(s) => Some(s.clone()),
+                _ => info.custom_domains,
+            };
+        }
+        if let Some(subdomain) = json_str(proxy, "subdomain") {
+            info.subdomain = Some(subdomain);
+        }
+        if let Some(local_ip) = json_str(proxy, "localIP") {
+            info.local_ip = Some(local_ip);
+        }
+        if let Some(local_port) = proxy.get("localPort").and_then(json_u16) {
+            info.local_port = Some(local_port);
+        }
+        if let Some(remote_port) = proxy.get("remotePort").and_then(json_u16) {
+            info.remote_port = Some(remote_port);
+        }
+    }
+
+    info
+}
+
+// 配置中的用户 token 和节点 token，用于日志脱敏
+/// 读取 frpc 配置文件中需要在日志里隐藏的 user 和 token
+pub fn read_config_secrets(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .map(|content| extract_config_secrets(&content, detect_config_format(&content)))
+        .unwrap_or_default()
+}
+
+fn extract_config_secrets(content: &str, format: ConfigFormat) -> Vec<String> {
+    let secrets: Vec<String> = match format {
+        ConfigFormat::Ini => content
+            .lines()
+            .filter_map(|line| parse_key_value(line.trim()))
+            .filter(|(key, _)| *key == "user" || *key == "token")
+            .map(|(_, value)| value.to_string())
+            .collect(),
+        _ => parse_structured_config(content, format)
+            .map(|root| {
+                [
+                    json_str(&root, "user"),
+                    root.get("auth").and_then(|auth| json_str(auth, "token")),
+                ]
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+            .unwrap_or_default(),
+    };
+
+    // 占位标记和环境变量引用不是 token
+    let mut secrets: Vec<String> = secrets
+        .into_iter()
+        .filter(|s| !s.is_empty() && !s.starts_with(SECRET_MARKER_PREFIX) && !s.starts_with("{{"))
+        .collect();
+    secrets.sort();
+    secrets.dedup();
+    secrets
+}
+
+fn json_str(value: &serde_json::Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+fn json_u16(value: &serde_json::Value) -> Option<u16> {
+    value
+        .as_u64()
+        .and_then(|n| u16::try_from(n).ok())
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
 fn save_custom_tunnel_list(
     app_handle: &tauri::AppHandle,
     tunnel: &CustomTunnel,
 ) -> Result<(), String> {
-    let app_dir = get_app_dir(app_handle)?;
-    let list_file = app_dir.join(TUNNELS_LIST_FILE);
+    settings::update(app_handle, |tunnels: &mut Vec<CustomTunnel>| {
+        if let Some(existing) = tunnels.iter_mut().find(|t| t.id == tunnel.id) {
+            *existing = tunnel.clone();
+        } else {
+            tunnels.push(tunnel.clone());
+        }
+        Ok(())
+    })
+    .map_err(|e| format!("保存自定义隧道列表失败: {}", e))
+}
 
-    let mut tunnels: Vec<CustomTunnel> = if list_file.exists() {
-        let content =
-            fs::read_to_string(&list_file).map_err(|e| format!("读取自定义隧道列表失败: {}", e))?;
-        serde_json::from_str(&content).map_err(|e| format!("解析自定义隧道列表失败: {}", e))?
-    } else {
-        Vec::new()
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML_TWO_PROXIES: &str = r#"serverAddr = "frp.example.com"
+serverPort = 7000
+
+[[visitors]]
+name = "visitor"
+type = "stcp"
+serverName = "secret"
+bindPort = 9000
+
+[[proxies]]
+name = "web"
+type = "tcp"
+localPort = 80
+remotePort = 8080
+
+[[proxies]]
+name = "ssh"
+type = "tcp"
+localPort = 22
+remotePort = 2222
+"#;
+
+    fn proxy_names(content: &str, format: ConfigFormat) -> Vec<String> {
+        let root = parse_structured_config(content, format).unwrap();
+        root["proxies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|proxy| proxy["name"].as_str().unwrap().to_string())
+            .collect()
+    }
 
-    if let Some(existing) = tunnels.iter_mut().find(|t| t.id == tunnel.id) {
-        *existing = tunnel.clone();
-    } else {
-        tunnels.push(tunnel.clone());
+    #[test]
+    fn detect_format() {
+        let cases = [
+            ("{\"serverAddr\": \"a\"}", ConfigFormat::Json),
+            ("  \n{}", ConfigFormat::Json),
+            ("[[proxies]]\nname = \"a\"", ConfigFormat::Toml),
+            ("[[visitors]]\nname = \"a\"", ConfigFormat::Toml),
+            ("serverAddr = \"a\"\nserverPort = 7000", ConfigFormat::Toml),
+            ("serverAddr: a\nserverPort: 7000", ConfigFormat::Yaml),
+            (
+                "[common]\nserver_addr = a\n\n[web]\ntype = tcp",
+                ConfigFormat::Ini,
+            ),
+            ("[web]\ntype = tcp", ConfigFormat::Ini),
+            ("", ConfigFormat::Ini),
+            ("not a config", ConfigFormat::Ini),
+        ];
+        for (content, expected) in cases {
+            assert_eq!(detect_config_format(content), expected, "{:?}", content);
+        }
     }
 
-    let content = serde_json::to_string_pretty(&tunnels)
-        .map_err(|e| format!("序列化自定义隧道列表失败: {}", e))?;
+    #[test]
+    fn split_single_proxy_keeps_content() {
+        let content = "# 注释\nserverAddr = \"a\"\n\n[[proxies]]\nname = \"web\"\ntype = \"tcp\"\n";
+        let split = split_structured_config(content, ConfigFormat::Toml).unwrap();
+        assert_eq!(split, vec![("web".to_string(), content.trim().to_string())]);
+    }
 
-    fs::write(&list_file, content).map_err(|e| format!("保存自定义隧道列表失败: {}", e))?;
+    #[test]
+    fn split_multiple_proxies() {
+        let cases = [
+            (TOML_TWO_PROXIES.to_string(), ConfigFormat::Toml),
+            (
+                serde_yaml::to_string(
+                    &parse_structured_config(TOML_TWO_PROXIES, ConfigFormat::Toml).unwrap(),
+                )
+                .unwrap(),
+                ConfigFormat::Yaml,
+            ),
+            (
+                serde_json::to_string(
+                    &parse_structured_config(TOML_TWO_PROXIES, ConfigFormat::Toml).unwrap(),
+                )
+                .unwrap(),
+                ConfigFormat::Json,
+            ),
+        ];
+        for (content, format) in cases {
+            let split = split_structured_config(&content, format).unwrap();
+            let names: Vec<&str> = split.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(names, ["web", "ssh"], "{:?}", format);
+
+            for (name, single) in &split {
+                assert_eq!(proxy_names(single, format), [name.as_str()], "{:?}", format);
+                let root = parse_structured_config(single, format).unwrap();
+                assert_eq!(root["serverAddr"], "frp.example.com", "{:?}", format);
+                // visitors 只保留在第一个配置中
+                assert_eq!(
+                    root.get("visitors").is_some(),
+                    name == "web",
+                    "{:?}",
+                    format
+                );
+            }
+        }
+    }
 
-    Ok(())
-}
+    #[test]
+    fn split_malformed_config() {
+        let cases = [
+            ("[[proxies]\nname = ", ConfigFormat::Toml),
+            ("proxies: [", ConfigFormat::Yaml),
+            ("{\"proxies\": ", ConfigFormat::Json),
+            ("[1, 2]", ConfigFormat::Json),
+            ("serverAddr = \"a\"", ConfigFormat::Ini),
+        ];
+        for (content, format) in cases {
+            assert!(
+                split_structured_config(content, format).is_err(),
+                "{:?}",
+                content
+            );
+        }
+
+        // 没有 proxies 或隧道缺少名称时不拆分出任何隧道
+        let cases = [
+            "serverAddr = \"a\"",
+            "[[proxies]]\ntype = \"tcp\"",
+            "proxies = \"web\"",
+        ];
+        for content in cases {
+            let split = split_structured_config(content, ConfigFormat::Toml).unwrap();
+            assert!(split.is_empty(), "{:?}", content);
+        }
+    }
 
-fn string_to_i32(s: &str) -> i32 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+    #[test]
+    fn tunnel_id_validation() {
+        for id in ["home", "my_tunnel-2", "隧道1"] {
+            assert!(is_valid_tunnel_id(id), "{}", id);
+        }
+        for id in ["", "..", "a/b", "a\\b", "C:", "a.ini", "a b"] {
+            assert!(!is_valid_tunnel_id(id), "{}", id);
+        }
+    }
 
-    let mut hasher = DefaultHasher::new();
-    s.hash(&mut hasher);
-    (hasher.finish() as i32).abs()
+    #[test]
+    fn seal_secrets_by_position() {
+        let cases = [
+            (
+                "[common]\nuser = abc\ntoken = xyz\n\n[abc]\ntype = tcp\nmeta_note = xyz\n",
+                "[common]\nuser = <0>\ntoken = <1>\n\n[abc]\ntype = tcp\nmeta_note = xyz\n",
+            ),
+            (
+                "user = \"abc\"\nauth.token = \"xyz\"\n\n[[proxies]]\nname = \"abc\"\n",
+                "user = \"<0>\"\nauth.token = \"<1>\"\n\n[[proxies]]\nname = \"abc\"\n",
+            ),
+            (
+                "user: abc\nauth:\n  token: 'xyz'\nproxies:\n  - name: abc-xyz\n",
+                "user: <0>\nauth:\n  token: '<1>'\nproxies:\n  - name: abc-xyz\n",
+            ),
+            (
+                r#"{"user":"abc","auth":{"token":"xyz"},"proxies":[{"name":"xyz"}]}"#,
+                r#"{"user":"<0>","auth":{"token":"<1>"},"proxies":[{"name":"xyz"}]}"#,
+            ),
+        ];
+
+        for (content, expected) in cases {
+            let secrets = extract_config_secrets(content, detect_config_format(content));
+            assert_eq!(secrets, ["abc", "xyz"], "{}", content);
+            let sealed =
+                replace_secret_values(content, &secrets, |index| format!("<{}>", index)).unwrap();
+            assert_eq!(sealed, expected);
+        }
+    }
+
+    #[test]
+    fn seal_secrets_requires_value_position() {
+        // 只在其他字段中出现的值无法定位，不能按全文替换
+        let err = replace_secret_values("name = abc\n", &["abc".to_string()], secret_marker);
+        assert!(err.is_err());
+
+        let runtime = replace_secret_values("token = abcdef\n", &["abcdef".to_string()], |_| {
+            token_vault::env_placeholder("CHMLFRP_SECRET_0")
+        })
+        .unwrap();
+        assert_eq!(runtime, "token = {{ .Envs.CHMLFRP_SECRET_0 }}\n");
+        assert!(extract_config_secrets(&runtime, ConfigFormat::Ini).is_empty());
+    }
 }
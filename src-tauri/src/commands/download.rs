@@ -1,9 +1,16 @@
-use crate::models::{DownloadInfo, DownloadProgress, FrpcDownload, FrpcInfoResponse};
+use crate::commands::download_job::{self, JobHandle};
+use crate::commands::frpc_config::{self, FrpcVersion};
+use crate::commands::{download_mirror, frpc_archive, frpc_version, process_adopt};
+use crate::models::{
+    DownloadInfo, DownloadMirror, DownloadProgress, FrpcDownload, FrpcInfoData, FrpcInfoResponse,
+    FrpcProcesses, FrpcUpdateInfo,
+};
 use futures_util::StreamExt;
 use sha2::{Digest, Sha256};
 use std::fs::OpenOptions;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use tauri::{Emitter, Manager};
 
 const MAX_RETRIES: u32 = 5;
@@ -73,7 +80,7 @@ fn matches_arch(os: &str, arch: &str, download_arch: &str) -> bool {
     }
 }
 
-fn verify_sha256(file_path: &Path, expected_hash: &str) -> Result<(), String> {
+pub fn compute_sha256(file_path: &Path) -> Result<String, String> {
     let mut file = std::fs::File::open(file_path)
         .map_err(|e| format!("无法打开文件进行 hash 验证: {}", e))?;
 
@@ -92,7 +99,11 @@ fn verify_sha256(file_path: &Path, expected_hash: &str) -> Result<(), String> {
         hasher.update(&buffer[..bytes_read]);
     }
 
-    let computed_hash = hex::encode(hasher.finalize());
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn verify_sha256(file_path: &Path, expected_hash: &str) -> Result<(), String> {
+    let computed_hash = compute_sha256(file_path)?;
 
     if computed_hash.to_lowercase() != expected_hash.to_lowercase() {
         return Err(format!(
@@ -118,11 +129,9 @@ fn set_executable_permission(file_path: &Path) -> Result<(), String> {
     Ok(())
 }
 
-pub async fn get_download_info() -> Result<DownloadInfo, String> {
-    let api_url = "https://cf-v1.uapis.cn/download/frpc/frpc_info.json";
-    let os = std::env::consts::OS;
-    let arch = std::env::consts::ARCH;
-
+// 获取镜像的 frpc 清单
+async fn fetch_manifest(mirror: &DownloadMirror) -> Result<FrpcInfoData, String> {
+    let api_url = &mirror.manifest_url;
     let client = build_http_client(DEFAULT_TIMEOUT)?;
 
     let response = client
@@ -147,12 +156,18 @@ pub async fn get_download_info() -> Result<DownloadInfo, String> {
         return Err(format!("API returned error: {}", info_response.msg));
     }
 
+    Ok(info_response.data)
+}
+
+// 从下载列表中选出当前平台的文件
+fn match_platform(downloads: &[FrpcDownload]) -> Result<&FrpcDownload, String> {
+    let os = std::env::consts::OS;
+    let arch = std::env::consts::ARCH;
+
     let platform = get_platform_string(os, arch)
         .ok_or_else(|| format!("Unsupported platform: {} {}", os, arch))?;
 
-    let mut matched_downloads: Vec<&FrpcDownload> = info_response
-        .data
-        .downloads
+    let mut matched_downloads: Vec<&FrpcDownload> = downloads
         .iter()
         .filter(|d| d.platform == platform)
         .collect();
@@ -160,27 +175,92 @@ pub async fn get_download_info() -> Result<DownloadInfo, String> {
     if matched_downloads.is_empty() {
         let target_os = if os == "macos" { "darwin" } else { os };
 
-        matched_downloads = info_response
-            .data
-            .downloads
+        matched_downloads = downloads
             .iter()
             .filter(|d| d.os == target_os && matches_arch(os, arch, &d.arch))
             .collect();
     }
 
-    let download = match matched_downloads.len() {
-        0 => return Err(format!("No matching download found for platform: {} {}", os, arch)),
-        1 => matched_downloads[0],
-        _ => matched_downloads.iter().max_by_key(|d| d.size).unwrap(),
+    match matched_downloads.len() {
+        0 => Err(format!(
+            "No matching download found for platform: {} {}",
+            os, arch
+        )),
+        1 => Ok(matched_downloads[0]),
+        _ => Ok(matched_downloads.iter().max_by_key(|d| d.size).unwrap()),
+    }
+}
+
+// 从清单中取出指定版本（为空时为最新版本）在当前平台的下载信息
+fn select_download(
+    mirror: &DownloadMirror,
+    data: &FrpcInfoData,
+    version: Option<&str>,
+) -> Result<DownloadInfo, String> {
+    let is_requested = |candidate: &str| match version {
+        Some(version) => frpc_version::normalize_version(candidate).is_ok_and(|v| v == version),
+        None => true,
+    };
+
+    let (release_version, release_notes, downloads) = if is_requested(&data.version) {
+        (&data.version, &data.release_notes, &data.downloads)
+    } else {
+        let release = data
+            .versions
+            .iter()
+            .find(|release| is_requested(&release.version))
+            .ok_or_else(|| {
+                format!(
+                    "下载源未提供 frpc {}，当前最新版本为 {}",
+                    version.unwrap_or_default(),
+                    data.version
+                )
+            })?;
+        (&release.version, &release.release_notes, &release.downloads)
     };
 
+    let download = match_platform(downloads)?;
     Ok(DownloadInfo {
-        url: download.link.clone(),
+        url: download_mirror::binary_url(mirror, &download.link),
         hash: download.hash.clone(),
         size: download.size,
+        binary_hash: download.binary_hash.clone(),
+        version: release_version.clone(),
+        release_notes: release_notes.clone(),
     })
 }
 
+// 从镜像的清单中取出当前平台最新版本的下载信息
+async fn fetch_download_info(mirror: &DownloadMirror) -> Result<DownloadInfo, String> {
+    let data = fetch_manifest(mirror).await?;
+    select_download(mirror, &data, None)
+}
+
+fn all_mirrors_failed(errors: Vec<String>) -> String {
+    format!("所有下载镜像均失败:\n{}", errors.join("\n"))
+}
+
+/// 依次尝试各下载镜像，返回第一个可用镜像的下载信息
+pub async fn get_download_info(
+    app_handle: &tauri::AppHandle,
+) -> Result<(DownloadMirror, DownloadInfo), String> {
+    let mut errors = Vec::new();
+    for mirror in download_mirror::ordered_mirrors(app_handle)? {
+        let started = Instant::now();
+        match fetch_download_info(&mirror).await {
+            Ok(info) => {
+                download_mirror::record_success(app_handle, &mirror.id, started.elapsed());
+                return Ok((mirror, info));
+            }
+            Err(e) => {
+                download_mirror::record_failure(app_handle, &mirror.id, &e);
+                errors.push(format!("{}: {}", mirror.name, e));
+            }
+        }
+    }
+    Err(all_mirrors_failed(errors))
+}
+
 #[tauri::command]
 pub async fn check_frpc_exists(app_handle: tauri::AppHandle) -> Result<bool, String> {
     let app_dir = app_handle
@@ -188,13 +268,7 @@ pub async fn check_frpc_exists(app_handle: tauri::AppHandle) -> Result<bool, Str
         .app_data_dir()
         .map_err(|e| e.to_string())?;
 
-    let frpc_path = if cfg!(target_os = "windows") {
-        app_dir.join("frpc.exe")
-    } else {
-        app_dir.join("frpc")
-    };
-
-    Ok(frpc_path.exists())
+    Ok(frpc_version::default_frpc_path(&app_dir).exists())
 }
 
 #[tauri::command]
@@ -208,31 +282,213 @@ pub async fn get_frpc_directory(app_handle: tauri::AppHandle) -> Result<String,
 }
 
 #[tauri::command]
-pub async fn get_download_url() -> Result<String, String> {
-    let info = get_download_info().await?;
+pub async fn get_download_url(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let (_, info) = get_download_info(&app_handle).await?;
     Ok(info.url)
 }
 
+/// 下载 frpc，指定 version 时安装到对应版本的目录，与默认的 frpc 并存
+///
+/// 指定的版本需为清单中的最新版本，或列在清单的 versions 中，否则无法下载
+///
+/// 有隧道正在使用目标 frpc 时拒绝替换，force 为 true 时仍然替换
 #[tauri::command]
-pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let download_info = get_download_info().await?;
-    let url = download_info.url;
-    let expected_hash = download_info.hash;
-    let expected_size = download_info.size;
+pub async fn download_frpc(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let version = version
+        .map(|version| frpc_version::normalize_version(&version))
+        .transpose()?;
 
     let app_dir = app_handle
         .path()
         .app_data_dir()
         .map_err(|e| e.to_string())?;
 
-    std::fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
-
-    let frpc_path = if cfg!(target_os = "windows") {
-        app_dir.join("frpc.exe")
-    } else {
-        app_dir.join("frpc")
+    let frpc_path = match &version {
+        Some(version) => frpc_version::version_frpc_path(&app_dir, version),
+        None => frpc_version::default_frpc_path(&app_dir),
     };
 
+    let force = force.unwrap_or(false);
+    install_frpc(&app_handle, version.as_deref(), &frpc_path, force).await?;
+
+    Ok(frpc_path.to_string_lossy().to_string())
+}
+
+// 运行中的隧道使用目标 frpc 时返回错误
+fn ensure_not_in_use(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    frpc_path: &Path,
+) -> Result<(), String> {
+    let running = process_adopt::running_keys(&app_handle.state::<FrpcProcesses>())?;
+    let in_use = frpc_version::tunnels_using(app_handle, app_dir, running, frpc_path)?;
+    if in_use.is_empty() {
+        return Ok(());
+    }
+
+    let names: Vec<String> = in_use.iter().map(|key| key.to_string()).collect();
+    Err(format!(
+        "{} 个隧道正在使用该 frpc ({})，请先停止这些隧道，或选择强制替换",
+        in_use.len(),
+        names.join(", ")
+    ))
+}
+
+// 依次尝试各镜像，直到 frpc 下载到暂存文件并通过校验
+async fn download_staged(
+    app_handle: &tauri::AppHandle,
+    job: &mut JobHandle,
+    version: Option<&str>,
+    staged_path: &Path,
+    frpc_path: &Path,
+) -> Result<DownloadInfo, String> {
+    let mut errors = Vec::new();
+    for mirror in download_mirror::ordered_mirrors(app_handle)? {
+        job.checkpoint().await?;
+        let started = Instant::now();
+        let data = match fetch_manifest(&mirror).await {
+            Ok(data) => data,
+            Err(e) => {
+                download_mirror::record_failure(app_handle, &mirror.id, &e);
+                errors.push(format!("{}: {}", mirror.name, e));
+                continue;
+            }
+        };
+
+        // 清单中没有所需的版本不是镜像故障，不计入健康状态
+        let download_info = match select_download(&mirror, &data, version) {
+            Ok(info) => info,
+            Err(e) => {
+                errors.push(format!("{}: {}", mirror.name, e));
+                continue;
+            }
+        };
+
+        let binary_hash = download_info.binary_hash.as_deref();
+        let downloaded = download_to_path(app_handle, job, &mirror, &download_info, staged_path)
+            .await
+            .and_then(|_| unpack_staged(staged_path, frpc_path, binary_hash));
+        match downloaded {
+            Ok(()) => {
+                download_mirror::record_success(app_handle, &mirror.id, started.elapsed());
+                return Ok(download_info);
+            }
+            // 取消时不再尝试其他镜像
+            Err(e) if job.is_cancelled() => {
+                let _ = std::fs::remove_file(staged_path);
+                return Err(e);
+            }
+            Err(e) => {
+                let _ = std::fs::remove_file(staged_path);
+                eprintln!("从镜像 {} 下载 frpc 失败: {}", mirror.name, e);
+                download_mirror::record_failure(app_handle, &mirror.id, &e);
+                errors.push(format!("{}: {}", mirror.name, e));
+            }
+        }
+    }
+    Err(all_mirrors_failed(errors))
+}
+
+// 登记下载任务，结束时发出最终状态
+async fn install_frpc(
+    app_handle: &tauri::AppHandle,
+    version: Option<&str>,
+    frpc_path: &Path,
+    force: bool,
+) -> Result<DownloadInfo, String> {
+    let mut job = download_job::register(app_handle, frpc_path)?;
+    let result = install_frpc_job(app_handle, &mut job, version, frpc_path, force).await;
+    job.finish(&result);
+    result
+}
+
+// 下载到临时文件并校验通过后再替换，失败时不影响原有的 frpc
+async fn install_frpc_job(
+    app_handle: &tauri::AppHandle,
+    job: &mut JobHandle,
+    version: Option<&str>,
+    frpc_path: &Path,
+    force: bool,
+) -> Result<DownloadInfo, String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    // 下载前先检查一次，避免下载完成后才发现无法替换
+    if !force {
+        ensure_not_in_use(app_handle, &app_dir, frpc_path)?;
+    }
+
+    if let Some(parent) = frpc_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let staged_path = staging_path(frpc_path);
+    let download_info = download_staged(app_handle, job, version, &staged_path, frpc_path).await?;
+
+    if job.is_cancelled() {
+        let _ = std::fs::remove_file(&staged_path);
+        return Err(download_job::CANCELLED_ERROR.to_string());
+    }
+    if !force {
+        if let Err(e) = ensure_not_in_use(app_handle, &app_dir, frpc_path) {
+            let _ = std::fs::remove_file(&staged_path);
+            return Err(e);
+        }
+    }
+    install_staged_binary(&staged_path, frpc_path)?;
+    Ok(download_info)
+}
+
+// 下载的是压缩包时解出 frpc 替换暂存文件，之后与直接下载可执行文件的流程相同
+fn unpack_staged(
+    staged_path: &Path,
+    frpc_path: &Path,
+    binary_hash: Option<&str>,
+) -> Result<(), String> {
+    if let Some(kind) = frpc_archive::detect(staged_path)? {
+        let unpack_path = sibling_path(frpc_path, ".unpack");
+        let binary_name = frpc_version::frpc_file_name();
+        let unpacked = frpc_archive::extract_binary(staged_path, kind, binary_name, &unpack_path)
+            .and_then(|_| {
+                std::fs::rename(&unpack_path, staged_path)
+                    .map_err(|e| format!("解压 frpc 失败: {}", e))
+            });
+        if let Err(e) = unpacked {
+            let _ = std::fs::remove_file(&unpack_path);
+            return Err(e);
+        }
+
+        match binary_hash {
+            Some(_) => eprintln!("压缩包 hash 验证成功，开始验证 frpc hash..."),
+            None => eprintln!("下载源未提供 frpc 的 hash，仅验证了压缩包"),
+        }
+    }
+
+    if let Some(binary_hash) = binary_hash {
+        verify_sha256(staged_path, binary_hash)?;
+    }
+
+    set_executable_permission(staged_path)
+}
+
+// 下载 frpc 到指定路径并校验 hash
+async fn download_to_path(
+    app_handle: &tauri::AppHandle,
+    job: &mut JobHandle,
+    mirror: &DownloadMirror,
+    download_info: &DownloadInfo,
+    frpc_path: &Path,
+) -> Result<(), String> {
+    let url = &download_info.url;
+    let expected_hash = &download_info.hash;
+    let expected_size = download_info.size;
+
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(DOWNLOAD_TIMEOUT))
         .connect_timeout(std::time::Duration::from_secs(CONNECT_TIMEOUT))
@@ -256,7 +512,7 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
     let mut total_size: u64 = expected_size;
 
     if total_size == 0 {
-        if let Ok(head_response) = client.head(&url).send().await {
+        if let Ok(head_response) = client.head(url).send().await {
             if let Some(len) = head_response.content_length() {
                 total_size = len;
             }
@@ -267,14 +523,14 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
         .create(true)
         .write(true)
         .truncate(true)
-        .open(&frpc_path)
+        .open(frpc_path)
         .map_err(|e| format!("无法打开文件进行写入: {}", e))?;
 
     let mut downloaded: u64 = 0;
     let mut retry_count = 0;
 
     loop {
-        let mut request = client.get(&url);
+        let mut request = client.get(url);
 
         if downloaded == 0 && total_size == 0 {
             request = request.header("Range", format!("bytes=0-{}", CHUNK_SIZE - 1));
@@ -290,14 +546,23 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
             request = request.header("Range", format!("bytes=0-{}", end));
         }
 
-        let response = match request.send().await {
+        let sent = tokio::select! {
+            sent = request.send() => Some(sent),
+            _ = job.interrupted() => None,
+        };
+        let Some(sent) = sent else {
+            job.checkpoint().await?;
+            continue;
+        };
+
+        let response = match sent {
             Ok(resp) => resp,
             Err(e) => {
                 retry_count += 1;
                 if retry_count >= MAX_RETRIES {
                     return Err(format!("下载失败，已重试 {} 次: {}", MAX_RETRIES, e));
                 }
-                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+                job.sleep(std::time::Duration::from_secs(2)).await?;
                 continue;
             }
         };
@@ -329,9 +594,21 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
 
         let mut stream = response.bytes_stream();
         let mut chunk_error = false;
+        let mut interrupted = false;
         let mut this_chunk_size: u64 = 0;
 
-        while let Some(item) = stream.next().await {
+        loop {
+            let item = tokio::select! {
+                item = stream.next() => item,
+                _ = job.interrupted() => {
+                    interrupted = true;
+                    break;
+                }
+            };
+            let Some(item) = item else {
+                break;
+            };
+
             match item {
                 Ok(chunk) => {
                     if let Err(e) = file.write_all(&chunk) {
@@ -358,6 +635,8 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
                                 downloaded,
                                 total: total_size,
                                 percentage,
+                                mirror: mirror.name.clone(),
+                                job_id: job.id,
                             },
                         );
                         this_chunk_size = 0;
@@ -370,6 +649,14 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
             }
         }
 
+        // 暂停时断开连接并保留已下载的部分，恢复后通过 Range 请求从断点继续
+        if interrupted {
+            drop(stream);
+            file.flush().map_err(|e| format!("刷新文件失败: {}", e))?;
+            job.checkpoint().await?;
+            continue;
+        }
+
         if !chunk_error {
             if total_size > 0 && downloaded >= total_size {
                 break;
@@ -387,7 +674,7 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
             if retry_count >= MAX_RETRIES {
                 return Err(format!("下载失败，已重试 {} 次", MAX_RETRIES));
             }
-            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            job.sleep(std::time::Duration::from_secs(2)).await?;
         }
     }
 
@@ -399,6 +686,8 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
             downloaded,
             total: total_size,
             percentage: 100.0,
+            mirror: mirror.name.clone(),
+            job_id: job.id,
         },
     );
 
@@ -414,13 +703,172 @@ pub async fn download_frpc(app_handle: tauri::AppHandle) -> Result<String, Strin
     }
 
     eprintln!("开始验证文件 hash...");
-    if let Err(e) = verify_sha256(&frpc_path, &expected_hash) {
-        let _ = std::fs::remove_file(&frpc_path);
+    if let Err(e) = verify_sha256(frpc_path, expected_hash) {
+        let _ = std::fs::remove_file(frpc_path);
         return Err(e);
     }
     eprintln!("文件 hash 验证成功");
+    Ok(())
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 升级前保留的旧版本 frpc
+pub fn backup_path(frpc_path: &Path) -> PathBuf {
+    sibling_path(frpc_path, ".bak")
+}
+
+// 下载中的临时文件，校验通过后才替换正式文件
+fn staging_path(frpc_path: &Path) -> PathBuf {
+    sibling_path(frpc_path, ".part")
+}
+
+// 回滚时复制备份用的临时文件，与下载的暂存文件分开，避免与进行中的下载互相覆盖
+fn rollback_staging_path(frpc_path: &Path) -> PathBuf {
+    sibling_path(frpc_path, ".rollback")
+}
+
+// 先为旧版本建立硬链接作为备份，再通过重命名原子替换，frpc 路径上始终是完整的可执行文件
+#[cfg(unix)]
+fn install_staged_binary(staged_path: &Path, frpc_path: &Path) -> Result<(), String> {
+    if frpc_path.exists() {
+        let backup = backup_path(frpc_path);
+        let linked = match std::fs::remove_file(&backup) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => std::fs::hard_link(frpc_path, &backup),
+        };
+        if let Err(e) = linked {
+            let _ = std::fs::remove_file(staged_path);
+            return Err(format!("备份旧版本 frpc 失败: {}", e));
+        }
+    }
+
+    std::fs::rename(staged_path, frpc_path).map_err(|e| {
+        let _ = std::fs::remove_file(staged_path);
+        format!("替换 frpc 失败: {}", e)
+    })
+}
+
+// Windows 上无法覆盖运行中的 frpc，先将旧版本重命名为备份再移入新文件；移入失败时恢复旧版本
+#[cfg(not(unix))]
+fn install_staged_binary(staged_path: &Path, frpc_path: &Path) -> Result<(), String> {
+    let backup = backup_path(frpc_path);
+    let has_current = frpc_path.exists();
+    if has_current {
+        std::fs::rename(frpc_path, &backup).map_err(|e| {
+            let _ = std::fs::remove_file(staged_path);
+            format!("备份旧版本 frpc 失败: {}", e)
+        })?;
+    }
+
+    std::fs::rename(staged_path, frpc_path).map_err(|e| {
+        let _ = std::fs::remove_file(staged_path);
+        if has_current {
+            if let Err(restore) = std::fs::rename(&backup, frpc_path) {
+                return format!("替换 frpc 失败: {}，恢复旧版本也失败: {}", e, restore);
+            }
+        }
+        format!("替换 frpc 失败: {}", e)
+    })
+}
+
+// 已安装的 frpc 版本，无法运行时返回 None
+fn installed_version(app_dir: &Path) -> Option<FrpcVersion> {
+    let frpc_path = frpc_version::default_frpc_path(app_dir);
+    if !frpc_path.exists() {
+        return None;
+    }
+    frpc_config::detect_frpc_version(&frpc_path)
+}
+
+/// 比较已安装的 frpc 与下载源中的最新版本
+#[tauri::command]
+pub async fn check_frpc_update(app_handle: tauri::AppHandle) -> Result<FrpcUpdateInfo, String> {
+    let (_, download_info) = get_download_info(&app_handle).await?;
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let installed = tokio::task::spawn_blocking(move || installed_version(&app_dir))
+        .await
+        .map_err(|e| format!("检测 frpc 版本失败: {}", e))?;
+    let latest = FrpcVersion::parse(&download_info.version);
+
+    // 未安装或无法识别版本时视为需要更新
+    let update_available = match (installed, latest) {
+        (Some(installed), Some(latest)) => latest > installed,
+        (None, Some(_)) => true,
+        (_, None) => false,
+    };
+
+    Ok(FrpcUpdateInfo {
+        installed_version: installed.map(|v| v.to_string()),
+        latest_version: download_info.version,
+        update_available,
+        release_notes: download_info.release_notes,
+    })
+}
+
+/// 将默认的 frpc 升级到最新版本，旧版本保留为 .bak
+#[tauri::command]
+pub async fn upgrade_frpc(
+    app_handle: tauri::AppHandle,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let frpc_path = frpc_version::default_frpc_path(&app_dir);
+    let force = force.unwrap_or(false);
+    let download_info = install_frpc(&app_handle, None, &frpc_path, force).await?;
+
+    Ok(download_info.version)
+}
+
+/// 恢复替换前的 frpc，当前版本会成为新的备份，再次回滚即可撤销
+#[tauri::command]
+pub async fn rollback_frpc(
+    app_handle: tauri::AppHandle,
+    version: Option<String>,
+    force: Option<bool>,
+) -> Result<String, String> {
+    let app_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?;
+
+    let frpc_path = match version {
+        Some(version) => {
+            frpc_version::version_frpc_path(&app_dir, &frpc_version::normalize_version(&version)?)
+        }
+        None => frpc_version::default_frpc_path(&app_dir),
+    };
+    let backup = backup_path(&frpc_path);
+    if !backup.exists() {
+        return Err("没有可回滚的 frpc 备份".to_string());
+    }
+
+    // 下载任务完成时会替换同一文件，回滚需等待任务结束
+    download_job::ensure_idle(&app_handle, &frpc_path)?;
+
+    if !force.unwrap_or(false) {
+        ensure_not_in_use(&app_handle, &app_dir, &frpc_path)?;
+    }
 
-    set_executable_permission(&frpc_path)?;
+    // 先复制到临时文件再替换，当前版本会成为新的备份
+    let staged_path = rollback_staging_path(&frpc_path);
+    std::fs::copy(&backup, &staged_path).map_err(|e| {
+        let _ = std::fs::remove_file(&staged_path);
+        format!("读取 frpc 备份失败: {}", e)
+    })?;
+    install_staged_binary(&staged_path, &frpc_path)?;
 
     Ok(frpc_path.to_string_lossy().to_string())
 }
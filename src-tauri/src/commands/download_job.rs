@@ -0,0 +1,229 @@
+use crate::models::{
+    DownloadControl, DownloadJob, DownloadJobInfo, DownloadJobs, DownloadStatus,
+    DownloadStatusEvent,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::watch;
+
+pub const CANCELLED_ERROR: &str = "下载已取消";
+
+fn emit_status(
+    app_handle: &tauri::AppHandle,
+    job_id: u64,
+    status: DownloadStatus,
+    error: Option<String>,
+) {
+    let _ = app_handle.emit(
+        "download-status",
+        DownloadStatusEvent {
+            job_id,
+            status,
+            error,
+        },
+    );
+}
+
+fn job_status(control: DownloadControl) -> DownloadStatus {
+    match control {
+        DownloadControl::Run => DownloadStatus::Running,
+        DownloadControl::Pause => DownloadStatus::Paused,
+        DownloadControl::Cancel => DownloadStatus::Cancelled,
+    }
+}
+
+/// 下载任务的句柄，下载过程中通过它响应暂停和取消
+pub struct JobHandle {
+    app_handle: tauri::AppHandle,
+    pub id: u64,
+    control: watch::Receiver<DownloadControl>,
+}
+
+// 同一 frpc 已有进行中或暂停的任务时返回错误
+fn check_idle(jobs: &HashMap<u64, DownloadJob>, frpc_path: &Path) -> Result<(), String> {
+    match jobs.iter().find(|(_, job)| job.frpc_path == frpc_path) {
+        Some((job_id, _)) => Err(format!("该 frpc 已有下载任务 {} 正在进行", job_id)),
+        None => Ok(()),
+    }
+}
+
+/// 替换 frpc 前调用，同一 frpc 有进行中或暂停的下载任务时返回错误
+pub fn ensure_idle(app_handle: &tauri::AppHandle, frpc_path: &Path) -> Result<(), String> {
+    let jobs = app_handle.state::<DownloadJobs>();
+    let jobs = jobs
+        .jobs
+        .lock()
+        .map_err(|e| format!("获取下载任务锁失败: {}", e))?;
+    check_idle(&jobs, frpc_path)
+}
+
+/// 登记一个下载任务，同一 frpc 已有进行中或暂停的任务时返回错误
+pub fn register(app_handle: &tauri::AppHandle, frpc_path: &Path) -> Result<JobHandle, String> {
+    let jobs = app_handle.state::<DownloadJobs>();
+    let mut jobs_map = jobs
+        .jobs
+        .lock()
+        .map_err(|e| format!("获取下载任务锁失败: {}", e))?;
+    check_idle(&jobs_map, frpc_path)?;
+
+    let id = jobs.next_id.fetch_add(1, Ordering::SeqCst);
+    let (control, receiver) = watch::channel(DownloadControl::Run);
+    jobs_map.insert(
+        id,
+        DownloadJob {
+            frpc_path: frpc_path.to_path_buf(),
+            control,
+        },
+    );
+    drop(jobs_map);
+    emit_status(app_handle, id, DownloadStatus::Running, None);
+
+    Ok(JobHandle {
+        app_handle: app_handle.clone(),
+        id,
+        control: receiver,
+    })
+}
+
+impl JobHandle {
+    pub fn is_cancelled(&self) -> bool {
+        *self.control.borrow() == DownloadControl::Cancel
+    }
+
+    /// 暂停时等待恢复，任务被取消时返回错误
+    pub async fn checkpoint(&mut self) -> Result<(), String> {
+        loop {
+            match *self.control.borrow_and_update() {
+                DownloadControl::Run => return Ok(()),
+                DownloadControl::Cancel => return Err(CANCELLED_ERROR.to_string()),
+                DownloadControl::Pause => {}
+            }
+            if self.control.changed().await.is_err() {
+                return Err(CANCELLED_ERROR.to_string());
+            }
+        }
+    }
+
+    /// 收到暂停或取消时完成，用于打断进行中的请求
+    pub async fn interrupted(&mut self) {
+        loop {
+            if *self.control.borrow_and_update() != DownloadControl::Run {
+                return;
+            }
+            if self.control.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// 等待重试，期间响应暂停和取消
+    pub async fn sleep(&mut self, duration: Duration) -> Result<(), String> {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => {}
+            _ = self.interrupted() => {}
+        }
+        self.checkpoint().await
+    }
+
+    /// 移除任务并发出最终状态
+    pub fn finish<T>(self, result: &Result<T, String>) {
+        let jobs = self.app_handle.state::<DownloadJobs>();
+        if let Ok(mut jobs) = jobs.jobs.lock() {
+            jobs.remove(&self.id);
+        }
+
+        let (status, error) = match result {
+            Ok(_) => (DownloadStatus::Completed, None),
+            Err(_) if self.is_cancelled() => (DownloadStatus::Cancelled, None),
+            Err(e) => (DownloadStatus::Failed, Some(e.clone())),
+        };
+        emit_status(&self.app_handle, self.id, status, error);
+    }
+}
+
+// 修改任务的控制信号，from 不为空时仅在当前状态匹配时修改，返回是否修改
+fn set_control(
+    app_handle: &tauri::AppHandle,
+    job_id: u64,
+    from: Option<DownloadControl>,
+    to: DownloadControl,
+) -> Result<bool, String> {
+    let jobs = app_handle.state::<DownloadJobs>();
+    let jobs = jobs
+        .jobs
+        .lock()
+        .map_err(|e| format!("获取下载任务锁失败: {}", e))?;
+    let job = jobs
+        .get(&job_id)
+        .ok_or_else(|| format!("下载任务 {} 不存在或已结束", job_id))?;
+
+    let current = *job.control.borrow();
+    if current == DownloadControl::Cancel {
+        return Err(format!("下载任务 {} 已取消", job_id));
+    }
+    if from.is_some_and(|from| from != current) {
+        return Ok(false);
+    }
+    job.control.send_replace(to);
+    Ok(true)
+}
+
+/// 获取进行中的下载任务
+#[tauri::command]
+pub async fn list_downloads(app_handle: tauri::AppHandle) -> Result<Vec<DownloadJobInfo>, String> {
+    let jobs = app_handle.state::<DownloadJobs>();
+    let jobs = jobs
+        .jobs
+        .lock()
+        .map_err(|e| format!("获取下载任务锁失败: {}", e))?;
+
+    let mut list: Vec<DownloadJobInfo> = jobs
+        .iter()
+        .map(|(job_id, job)| DownloadJobInfo {
+            job_id: *job_id,
+            frpc_path: job.frpc_path.to_string_lossy().to_string(),
+            status: job_status(*job.control.borrow()),
+        })
+        .collect();
+    list.sort_by_key(|job| job.job_id);
+    Ok(list)
+}
+
+/// 取消下载任务，已下载的临时文件会被删除
+#[tauri::command]
+pub async fn cancel_download(app_handle: tauri::AppHandle, job_id: u64) -> Result<(), String> {
+    set_control(&app_handle, job_id, None, DownloadControl::Cancel)?;
+    Ok(())
+}
+
+/// 暂停下载任务，保留已下载的部分，恢复后从断点继续
+#[tauri::command]
+pub async fn pause_download(app_handle: tauri::AppHandle, job_id: u64) -> Result<(), String> {
+    let paused = set_control(
+        &app_handle,
+        job_id,
+        Some(DownloadControl::Run),
+        DownloadControl::Pause,
+    )?;
+    if paused {
+        emit_status(&app_handle, job_id, DownloadStatus::Paused, None);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resume_download(app_handle: tauri::AppHandle, job_id: u64) -> Result<(), String> {
+    let resumed = set_control(
+        &app_handle,
+        job_id,
+        Some(DownloadControl::Pause),
+        DownloadControl::Run,
+    )?;
+    if resumed {
+        emit_status(&app_handle, job_id, DownloadStatus::Running, None);
+    }
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use crate::commands::settings;
+use crate::models::{
+    DownloadMirror, DownloadMirrorStatus, DownloadMirrors, MirrorHealth, MirrorHealthStates,
+};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+const BUILTIN_MIRROR_ID: &str = "official";
+const BUILTIN_MANIFEST_URL: &str = "https://cf-v1.uapis.cn/download/frpc/frpc_info.json";
+// 连续失败达到该次数后进入冷却期
+const FAILURE_THRESHOLD: u32 = 2;
+const COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// 默认的下载源
+pub fn builtin_mirror() -> DownloadMirror {
+    DownloadMirror {
+        id: BUILTIN_MIRROR_ID.to_string(),
+        name: "ChmlFrp 官方".to_string(),
+        manifest_url: BUILTIN_MANIFEST_URL.to_string(),
+        binary_base_url: None,
+        disabled: false,
+    }
+}
+
+fn is_cooling_down(health: &MirrorHealth) -> bool {
+    health.consecutive_failures >= FAILURE_THRESHOLD
+        && health.failed_at.is_some_and(|at| at.elapsed() < COOLDOWN)
+}
+
+/// 按设置中的顺序返回启用的镜像，冷却期内的镜像排到最后
+pub fn ordered_mirrors(app_handle: &tauri::AppHandle) -> Result<Vec<DownloadMirror>, String> {
+    let store = settings::load::<DownloadMirrors>(app_handle)?;
+    let states = app_handle.state::<MirrorHealthStates>();
+    let health = states
+        .health
+        .lock()
+        .map_err(|e| format!("获取镜像状态锁失败: {}", e))?;
+
+    let (mut mirrors, cooling): (Vec<_>, Vec<_>) = store
+        .mirrors
+        .into_iter()
+        .filter(|mirror| !mirror.disabled)
+        .partition(|mirror| !health.get(&mirror.id).is_some_and(is_cooling_down));
+    mirrors.extend(cooling);
+
+    if mirrors.is_empty() {
+        return Err("没有可用的下载镜像，请在设置中启用至少一个镜像".to_string());
+    }
+    Ok(mirrors)
+}
+
+pub fn record_success(app_handle: &tauri::AppHandle, mirror_id: &str, latency: Duration) {
+    let states = app_handle.state::<MirrorHealthStates>();
+    let Ok(mut health) = states.health.lock() else {
+        return;
+    };
+    let entry = health.entry(mirror_id.to_string()).or_default();
+    entry.consecutive_failures = 0;
+    entry.last_success_at = Some(chrono::Local::now().to_rfc3339());
+    entry.last_latency_ms = Some(latency.as_millis() as u64);
+    entry.failed_at = None;
+}
+
+pub fn record_failure(app_handle: &tauri::AppHandle, mirror_id: &str, error: &str) {
+    let states = app_handle.state::<MirrorHealthStates>();
+    let Ok(mut health) = states.health.lock() else {
+        return;
+    };
+    let entry = health.entry(mirror_id.to_string()).or_default();
+    entry.consecutive_failures += 1;
+    entry.last_error = Some(error.to_string());
+    entry.last_failure_at = Some(chrono::Local::now().to_rfc3339());
+    entry.failed_at = Some(Instant::now());
+}
+
+/// 镜像设置了 binary_base_url 时，将清单中的下载链接改写到该地址下
+pub fn binary_url(mirror: &DownloadMirror, link: &str) -> String {
+    let Some(base) = mirror.binary_base_url.as_deref() else {
+        return link.to_string();
+    };
+    let path = link.split(['?', '#']).next().unwrap_or(link);
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    format!("{}/{}", base.trim_end_matches('/'), file_name)
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("无效的镜像地址 {}: {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("镜像地址仅支持 http 或 https: {}", url));
+    }
+    Ok(())
+}
+
+fn normalize_mirrors(mirrors: Vec<DownloadMirror>) -> Result<Vec<DownloadMirror>, String> {
+    if mirrors.is_empty() {
+        return Err("至少需要保留一个下载镜像".to_string());
+    }
+    if mirrors.iter().all(|mirror| mirror.disabled) {
+        return Err("至少需要启用一个下载镜像".to_string());
+    }
+
+    let mut ids = HashSet::new();
+    let mut normalized = Vec::with_capacity(mirrors.len());
+    for (index, mirror) in mirrors.into_iter().enumerate() {
+        let id = match mirror.id.trim() {
+            "" => format!("mirror_{}", index + 1),
+            id => id.to_string(),
+        };
+        if !ids.insert(id.clone()) {
+            return Err(format!("镜像 id 重复: {}", id));
+        }
+
+        let manifest_url = mirror.manifest_url.trim().to_string();
+        validate_url(&manifest_url)?;
+        let binary_base_url = match mirror.binary_base_url.as_deref().map(str::trim) {
+            Some("") | None => None,
+            Some(url) => {
+                validate_url(url)?;
+                Some(url.to_string())
+            }
+        };
+
+        let name = match mirror.name.trim() {
+            "" => id.clone(),
+            name => name.to_string(),
+        };
+        normalized.push(DownloadMirror {
+            id,
+            name,
+            manifest_url,
+            binary_base_url,
+            disabled: mirror.disabled,
+        });
+    }
+    Ok(normalized)
+}
+
+/// 获取下载镜像列表及其健康状态，按设置中的顺序排列
+#[tauri::command]
+pub async fn get_download_mirrors(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<DownloadMirrorStatus>, String> {
+    let store = settings::load::<DownloadMirrors>(&app_handle)?;
+    let states = app_handle.state::<MirrorHealthStates>();
+    let health = states
+        .health
+        .lock()
+        .map_err(|e| format!("获取镜像状态锁失败: {}", e))?;
+
+    Ok(store
+        .mirrors
+        .into_iter()
+        .map(|mirror| {
+            let health = health.get(&mirror.id).cloned().unwrap_or_default();
+            DownloadMirrorStatus {
+                cooling_down: is_cooling_down(&health),
+                mirror,
+                health,
+            }
+        })
+        .collect())
+}
+
+/// 保存下载镜像列表，下载时按列表顺序尝试
+#[tauri::command]
+pub async fn set_download_mirrors(
+    app_handle: tauri::AppHandle,
+    mirrors: Vec<DownloadMirror>,
+) -> Result<(), String> {
+    let mirrors = normalize_mirrors(mirrors)?;
+    settings::update(&app_handle, |store: &mut DownloadMirrors| {
+        store.mirrors = mirrors;
+        Ok(())
+    })
+}
+
+/// 恢复为默认的下载镜像，并清除健康状态
+#[tauri::command]
+pub async fn reset_download_mirrors(app_handle: tauri::AppHandle) -> Result<(), String> {
+    settings::update(&app_handle, |store: &mut DownloadMirrors| {
+        store.mirrors = vec![builtin_mirror()];
+        Ok(())
+    })?;
+
+    let states = app_handle.state::<MirrorHealthStates>();
+    let mut health = states
+        .health
+        .lock()
+        .map_err(|e| format!("获取镜像状态锁失败: {}", e))?;
+    health.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mirror(id: &str, manifest_url: &str) -> DownloadMirror {
+        DownloadMirror {
+            id: id.to_string(),
+            name: String::new(),
+            manifest_url: manifest_url.to_string(),
+            binary_base_url: None,
+            disabled: false,
+        }
+    }
+
+    #[test]
+    fn binary_url_keeps_link_without_base() {
+        let link = "https://cf-v1.uapis.cn/download/frpc/frp_0.51.2_linux_amd64.tar.gz";
+        assert_eq!(binary_url(&builtin_mirror(), link), link);
+    }
+
+    #[test]
+    fn binary_url_rewrites_file_name_onto_base() {
+        let mut mirror = mirror("cdn", "https://cdn.example.com/frpc_info.json");
+        let cases = [
+            ("https://a.example.com/x/frpc.tar.gz", "frpc.tar.gz"),
+            ("https://a.example.com/x/frpc.zip?sign=1#top", "frpc.zip"),
+            ("frpc_linux", "frpc_linux"),
+        ];
+        for base in [
+            "https://cdn.example.com/frp",
+            "https://cdn.example.com/frp/",
+        ] {
+            mirror.binary_base_url = Some(base.to_string());
+            for (link, file_name) in cases {
+                assert_eq!(
+                    binary_url(&mirror, link),
+                    format!("https://cdn.example.com/frp/{}", file_name),
+                    "{} {}",
+                    base,
+                    link
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn normalize_fills_ids_and_names() {
+        let mut second = mirror(" ", " https://b.example.com/frpc_info.json ");
+        second.binary_base_url = Some("  ".to_string());
+        let mut first = mirror(" main ", "https://a.example.com/frpc_info.json");
+        first.name = " 主镜像 ".to_string();
+
+        let mirrors = normalize_mirrors(vec![first, second]).unwrap();
+        assert_eq!(mirrors[0].id, "main");
+        assert_eq!(mirrors[0].name, "主镜像");
+        assert_eq!(mirrors[1].id, "mirror_2");
+        assert_eq!(mirrors[1].name, "mirror_2");
+        assert_eq!(
+            mirrors[1].manifest_url,
+            "https://b.example.com/frpc_info.json"
+        );
+        assert_eq!(mirrors[1].binary_base_url, None);
+    }
+
+    #[test]
+    fn normalize_rejects_invalid_lists() {
+        let url = "https://a.example.com/frpc_info.json";
+        let mut disabled = mirror("a", url);
+        disabled.disabled = true;
+        let mut bad_base = mirror("a", url);
+        bad_base.binary_base_url = Some("ftp://a.example.com".to_string());
+
+        let cases = [
+            vec![],
+            vec![disabled],
+            vec![mirror("a", url), mirror(" a ", url)],
+            vec![mirror("a", "not a url")],
+            vec![mirror("a", "file:///etc/frpc_info.json")],
+            vec![bad_base],
+        ];
+        for mirrors in cases {
+            let ids: Vec<String> = mirrors.iter().map(|m| m.id.clone()).collect();
+            assert!(normalize_mirrors(mirrors).is_err(), "{:?}", ids);
+        }
+    }
+}
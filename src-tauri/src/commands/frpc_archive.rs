@@ -0,0 +1,214 @@
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+// frp 官方发布的压缩包格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    TarGz,
+    Zip,
+}
+
+/// 根据文件头判断下载的文件是否为压缩包，下载链接不一定带有扩展名
+pub fn detect(path: &Path) -> Result<Option<ArchiveKind>, String> {
+    let file = File::open(path).map_err(|e| format!("读取下载文件失败: {}", e))?;
+    let mut header = Vec::with_capacity(ZIP_MAGIC.len());
+    file.take(ZIP_MAGIC.len() as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| format!("读取下载文件失败: {}", e))?;
+
+    if header.starts_with(GZIP_MAGIC) {
+        Ok(Some(ArchiveKind::TarGz))
+    } else if header.starts_with(ZIP_MAGIC) {
+        Ok(Some(ArchiveKind::Zip))
+    } else {
+        Ok(None)
+    }
+}
+
+fn write_member(reader: &mut impl Read, dest: &Path) -> Result<(), String> {
+    let mut file = File::create(dest).map_err(|e| format!("无法创建 frpc 文件: {}", e))?;
+    std::io::copy(reader, &mut file).map_err(|e| format!("解压 frpc 失败: {}", e))?;
+    file.sync_all()
+        .map_err(|e| format!("解压 frpc 失败: {}", e))
+}
+
+// 压缩包内通常是 frp_<版本>_<系统>_<架构>/frpc，只按文件名匹配
+fn is_binary(path: &Path, binary_name: &str) -> bool {
+    path.file_name().and_then(|name| name.to_str()) == Some(binary_name)
+}
+
+fn extract_from_tar_gz(archive: &Path, binary_name: &str, dest: &Path) -> Result<bool, String> {
+    let file = File::open(archive).map_err(|e| format!("读取压缩包失败: {}", e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("读取压缩包失败: {}", e))?;
+
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("读取压缩包失败: {}", e))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .map_err(|e| format!("读取压缩包失败: {}", e))?
+            .into_owned();
+        if is_binary(&path, binary_name) {
+            write_member(&mut entry, dest)?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn extract_from_zip(archive: &Path, binary_name: &str, dest: &Path) -> Result<bool, String> {
+    let file = File::open(archive).map_err(|e| format!("读取压缩包失败: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("读取压缩包失败: {}", e))?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive
+            .by_index(index)
+            .map_err(|e| format!("读取压缩包失败: {}", e))?;
+        if !entry.is_file() {
+            continue;
+        }
+        let Some(path) = entry.enclosed_name() else {
+            continue;
+        };
+        if is_binary(&path, binary_name) {
+            write_member(&mut entry, dest)?;
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// 从压缩包中解出 frpc 到 dest
+pub fn extract_binary(
+    archive: &Path,
+    kind: ArchiveKind,
+    binary_name: &str,
+    dest: &Path,
+) -> Result<(), String> {
+    let found = match kind {
+        ArchiveKind::TarGz => extract_from_tar_gz(archive, binary_name, dest),
+        ArchiveKind::Zip => extract_from_zip(archive, binary_name, dest),
+    };
+
+    match found {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("压缩包中未找到 {}", binary_name)),
+        Err(e) => {
+            let _ = std::fs::remove_file(dest);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    const BINARY: &[u8] = b"\x7fELF frpc";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("chmlfrp-archive-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_tar_gz(path: &Path, members: &[(&str, &[u8])]) {
+        let encoder =
+            flate2::write::GzEncoder::new(File::create(path).unwrap(), Default::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn write_zip(path: &Path, members: &[(&str, &[u8])]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, data) in members {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn extract_nested_binary_from_tar_gz() {
+        let dir = temp_dir("tar-gz");
+        let archive = dir.join("frp.tar.gz");
+        write_tar_gz(
+            &archive,
+            &[
+                ("frp_0.51.2_linux_amd64/frpc.toml", b"serverAddr = \"\""),
+                ("frp_0.51.2_linux_amd64/frpc", BINARY),
+                ("frp_0.51.2_linux_amd64/frps", b"frps"),
+            ],
+        );
+        assert_eq!(detect(&archive), Ok(Some(ArchiveKind::TarGz)));
+
+        let dest = dir.join("frpc");
+        extract_binary(&archive, ArchiveKind::TarGz, "frpc", &dest).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), BINARY);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extract_nested_binary_from_zip() {
+        let dir = temp_dir("zip");
+        let archive = dir.join("frp.zip");
+        write_zip(
+            &archive,
+            &[
+                ("frp_0.51.2_windows_amd64/frpc.exe.sig", b"sig"),
+                ("frp_0.51.2_windows_amd64/frpc.exe", BINARY),
+            ],
+        );
+        assert_eq!(detect(&archive), Ok(Some(ArchiveKind::Zip)));
+
+        let dest = dir.join("frpc.exe");
+        extract_binary(&archive, ArchiveKind::Zip, "frpc.exe", &dest).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), BINARY);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_binary_is_an_error() {
+        let dir = temp_dir("missing");
+        let archive = dir.join("frp.zip");
+        write_zip(&archive, &[("frp_0.51.2_linux_amd64/frps", b"frps")]);
+
+        let dest = dir.join("frpc");
+        let err = extract_binary(&archive, ArchiveKind::Zip, "frpc", &dest).unwrap_err();
+        assert!(err.contains("frpc"), "{}", err);
+        assert!(!dest.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn plain_binary_is_not_an_archive() {
+        let dir = temp_dir("plain");
+        let path = dir.join("frpc");
+        std::fs::write(&path, BINARY).unwrap();
+        assert_eq!(detect(&path), Ok(None));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
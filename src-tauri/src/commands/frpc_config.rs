@@ -0,0 +1,417 @@
+use crate::models::{TunnelConfig, TunnelKey};
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command as StdCommand;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+// frpc 从 v0.52.0 开始支持 TOML/YAML/JSON 配置，INI 格式被标记为废弃
+const STRUCTURED_CONFIG_MIN_VERSION: FrpcVersion = FrpcVersion {
+    major: 0,
+    minor: 52,
+    patch: 0,
+};
+
+// 配置文件格式
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Ini,
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub const ALL: [ConfigFormat; 4] = [
+        ConfigFormat::Ini,
+        ConfigFormat::Toml,
+        ConfigFormat::Yaml,
+        ConfigFormat::Json,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "ini" => Some(ConfigFormat::Ini),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Ini => "ini",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+        }
+    }
+
+    pub fn for_version(version: Option<FrpcVersion>) -> Self {
+        match version {
+            Some(v) if v >= STRUCTURED_CONFIG_MIN_VERSION => ConfigFormat::Toml,
+            _ => ConfigFormat::Ini,
+        }
+    }
+}
+
+// frpc 版本号
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FrpcVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl FrpcVersion {
+    /// 解析 `0.51.3`、`v0.58.1` 或 `0.51.2_chmlfrp` 这类版本字符串
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix('v').unwrap_or(raw);
+        let numeric: String = raw
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+
+        let mut parts = numeric.split('.').filter(|p| !p.is_empty());
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+        let patch = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+}
+
+impl std::fmt::Display for FrpcVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// 运行 `frpc -v` 获取已安装的 frpc 版本
+pub fn detect_frpc_version(frpc_path: &Path) -> Option<FrpcVersion> {
+    let mut cmd = StdCommand::new(frpc_path);
+    cmd.arg("-v");
+
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(0x08000000);
+    }
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(FrpcVersion::parse)
+}
+
+pub fn config_file_name(tunnel_key: &TunnelKey, format: ConfigFormat) -> String {
+    format!("g_{}.{}", tunnel_key, format.extension())
+}
+
+pub fn remove_generated_configs(app_dir: &Path, tunnel_key: &TunnelKey) {
+    for format in ConfigFormat::ALL {
+        let config_path = app_dir.join(config_file_name(tunnel_key, format));
+        if config_path.exists() {
+            let _ = std::fs::remove_file(&config_path);
+        }
+    }
+}
+
+// 删除所有启动隧道时生成的配置文件
+pub fn cleanup_generated_configs(app_dir: &Path, keep: &HashSet<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(app_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if let Ok(file_name) = entry.file_name().into_string() {
+            let is_generated_config = ConfigFormat::ALL
+                .iter()
+                .any(|format| file_name.ends_with(&format!(".{}", format.extension())));
+            if file_name.starts_with("g_") && is_generated_config && !keep.contains(&entry.path()) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+pub fn generate_frpc_config(config: &TunnelConfig, format: ConfigFormat) -> Result<String, String> {
+    validate_tunnel_config(config)?;
+
+    let content = match format {
+        ConfigFormat::Ini => render_ini(config),
+        ConfigFormat::Toml => render_toml(config),
+        ConfigFormat::Yaml => render_yaml(config),
+        ConfigFormat::Json => render_json(config),
+    };
+
+    Ok(content)
+}
+
+fn validate_tunnel_config(config: &TunnelConfig) -> Result<(), String> {
+    match config.tunnel_type.as_str() {
+        "tcp" | "udp" if config.remote_port.is_none() => {
+            Err("TCP/UDP 隧道缺少 remote_port 参数".to_string())
+        }
+        "http" | "https" if config.custom_domains.is_none() => {
+            Err("HTTP/HTTPS 隧道缺少 custom_domains 参数".to_string())
+        }
+        "tcp" | "udp" | "http" | "https" => Ok(()),
+        _ => Err(format!("不支持的隧道类型: {}", config.tunnel_type)),
+    }
+}
+
+fn use_kcp(config: &TunnelConfig) -> bool {
+    config.kcp_optimization && is_port_tunnel(config)
+}
+
+fn split_domains(domains: &str) -> Vec<&str> {
+    domains
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .collect()
+}
+
+// JSON 字符串同时是合法的 TOML 基本字符串和 YAML 双引号字符串
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+fn render_ini(config: &TunnelConfig) -> String {
+    let mut content = String::new();
+
+    writeln!(content, "[common]").unwrap();
+    writeln!(content, "server_addr = {}", config.server_addr).unwrap();
+    writeln!(content, "server_port = {}", config.server_port).unwrap();
+
+    if let Some(ref proxy_url) = config.http_proxy {
+        writeln!(content, "http_proxy = {}", proxy_url).unwrap();
+    }
+
+    writeln!(content, "log_level = {}", config.log_level).unwrap();
+    writeln!(content, "tls_enable = {}", config.force_tls).unwrap();
+    writeln!(content, "tcp_mux = true").unwrap();
+    writeln!(content, "pool_count = 5").unwrap();
+
+    if use_kcp(config) {
+        writeln!(content, "protocol = kcp").unwrap();
+    }
+
+    writeln!(content, "user = {}", config.user_token).unwrap();
+    writeln!(content, "token = {}", config.node_token).unwrap();
+    writeln!(content).unwrap();
+
+    writeln!(content, "[{}]", config.tunnel_name).unwrap();
+    writeln!(content, "type = {}", config.tunnel_type).unwrap();
+    writeln!(content, "local_ip = {}", config.local_ip).unwrap();
+    writeln!(content, "local_port = {}", config.local_port).unwrap();
+
+    if is_port_tunnel(config) {
+        if let Some(remote_port) = config.remote_port {
+            writeln!(content, "remote_port = {}", remote_port).unwrap();
+        }
+    } else if let Some(ref custom_domains) = config.custom_domains {
+        writeln!(content, "custom_domains = {}", custom_domains).unwrap();
+    }
+
+    content
+}
+
+fn render_toml(config: &TunnelConfig) -> String {
+    let mut content = String::new();
+
+    writeln!(content, "serverAddr = {}", quote(&config.server_addr)).unwrap();
+    writeln!(content, "serverPort = {}", config.server_port).unwrap();
+    writeln!(content, "user = {}", quote(&config.user_token)).unwrap();
+    writeln!(content, "auth.method = \"token\"").unwrap();
+    writeln!(content, "auth.token = {}", quote(&config.node_token)).unwrap();
+    writeln!(content, "log.level = {}", quote(&config.log_level)).unwrap();
+    writeln!(content, "transport.tls.enable = {}", config.force_tls).unwrap();
+    writeln!(content, "transport.tcpMux = true").unwrap();
+    writeln!(content, "transport.poolCount = 5").unwrap();
+
+    if let Some(ref proxy_url) = config.http_proxy {
+        writeln!(content, "transport.proxyURL = {}", quote(proxy_url)).unwrap();
+    }
+
+    if use_kcp(config) {
+        writeln!(content, "transport.protocol = \"kcp\"").unwrap();
+    }
+
+    writeln!(content).unwrap();
+    writeln!(content, "[[proxies]]").unwrap();
+    writeln!(content, "name = {}", quote(&config.tunnel_name)).unwrap();
+    writeln!(content, "type = {}", quote(&config.tunnel_type)).unwrap();
+    writeln!(content, "localIP = {}", quote(&config.local_ip)).unwrap();
+    writeln!(content, "localPort = {}", config.local_port).unwrap();
+
+    if is_port_tunnel(config) {
+        if let Some(remote_port) = config.remote_port {
+            writeln!(content, "remotePort = {}", remote_port).unwrap();
+        }
+    } else if let Some(ref custom_domains) = config.custom_domains {
+        let domains: Vec<String> = split_domains(custom_domains)
+            .into_iter()
+            .map(quote)
+            .collect();
+        writeln!(content, "customDomains = [{}]", domains.join(", ")).unwrap();
+    }
+
+    content
+}
+
+fn render_yaml(config: &TunnelConfig) -> String {
+    let mut content = String::new();
+
+    writeln!(content, "serverAddr: {}", quote(&config.server_addr)).unwrap();
+    writeln!(content, "serverPort: {}", config.server_port).unwrap();
+    writeln!(content, "user: {}", quote(&config.user_token)).unwrap();
+    writeln!(content, "auth:").unwrap();
+    writeln!(content, "  method: token").unwrap();
+    writeln!(content, "  token: {}", quote(&config.node_token)).unwrap();
+    writeln!(content, "log:").unwrap();
+    writeln!(content, "  level: {}", quote(&config.log_level)).unwrap();
+    writeln!(content, "transport:").unwrap();
+    writeln!(content, "  tls:").unwrap();
+    writeln!(content, "    enable: {}", config.force_tls).unwrap();
+    writeln!(content, "  tcpMux: true").unwrap();
+    writeln!(content, "  poolCount: 5").unwrap();
+
+    if let Some(ref proxy_url) = config.http_proxy {
+        writeln!(content, "  proxyURL: {}", quote(proxy_url)).unwrap();
+    }
+
+    if use_kcp(config) {
+        writeln!(content, "  protocol: kcp").unwrap();
+    }
+
+    writeln!(content).unwrap();
+    writeln!(content, "proxies:").unwrap();
+    writeln!(content, "  - name: {}", quote(&config.tunnel_name)).unwrap();
+    writeln!(content, "    type: {}", quote(&config.tunnel_type)).unwrap();
+    writeln!(content, "    localIP: {}", quote(&config.local_ip)).unwrap();
+    writeln!(content, "    localPort: {}", config.local_port).unwrap();
+
+    if is_port_tunnel(config) {
+        if let Some(remote_port) = config.remote_port {
+            writeln!(content, "    remotePort: {}", remote_port).unwrap();
+        }
+    } else if let Some(ref custom_domains) = config.custom_domains {
+        writeln!(content, "    customDomains:").unwrap();
+        for domain in split_domains(custom_domains) {
+            writeln!(content, "      - {}", quote(domain)).unwrap();
+        }
+    }
+
+    content
+}
+
+fn render_json(config: &TunnelConfig) -> String {
+    let mut transport = serde_json::json!({
+        "tls": { "enable": config.force_tls },
+        "tcpMux": true,
+        "poolCount": 5,
+    });
+
+    if let Some(ref proxy_url) = config.http_proxy {
+        transport["proxyURL"] = serde_json::json!(proxy_url);
+    }
+
+    if use_kcp(config) {
+        transport["protocol"] = serde_json::json!("kcp");
+    }
+
+    let mut proxy = serde_json::json!({
+        "name": config.tunnel_name,
+        "type": config.tunnel_type,
+        "localIP": config.local_ip,
+        "localPort": config.local_port,
+    });
+
+    if is_port_tunnel(config) {
+        if let Some(remote_port) = config.remote_port {
+            proxy["remotePort"] = serde_json::json!(remote_port);
+        }
+    } else if let Some(ref custom_domains) = config.custom_domains {
+        proxy["customDomains"] = serde_json::json!(split_domains(custom_domains));
+    }
+
+    let root = serde_json::json!({
+        "serverAddr": config.server_addr,
+        "serverPort": config.server_port,
+        "user": config.user_token,
+        "auth": { "method": "token", "token": config.node_token },
+        "log": { "level": config.log_level },
+        "transport": transport,
+        "proxies": [proxy],
+    });
+
+    serde_json::to_string_pretty(&root).unwrap_or_default()
+}
+
+fn is_port_tunnel(config: &TunnelConfig) -> bool {
+    config.tunnel_type == "tcp" || config.tunnel_type == "udp"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u32, minor: u32, patch: u32) -> FrpcVersion {
+        FrpcVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    #[test]
+    fn parse_frpc_version() {
+        let cases = [
+            ("0.51.3", Some(version(0, 51, 3))),
+            ("v0.58.1", Some(version(0, 58, 1))),
+            ("  0.52.0\n", Some(version(0, 52, 0))),
+            ("0.51.2_chmlfrp", Some(version(0, 51, 2))),
+            ("0.52", Some(version(0, 52, 0))),
+            ("1", Some(version(1, 0, 0))),
+            ("0.51.2.9", Some(version(0, 51, 2))),
+            ("", None),
+            ("v", None),
+            ("frpc 0.51.3", None),
+            (".", None),
+            ("99999999999.1.0", None),
+        ];
+        for (raw, expected) in cases {
+            assert_eq!(FrpcVersion::parse(raw), expected, "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn config_format_for_version() {
+        let cases = [
+            (None, ConfigFormat::Ini),
+            (Some(version(0, 51, 3)), ConfigFormat::Ini),
+            (Some(version(0, 52, 0)), ConfigFormat::Toml),
+            (Some(version(1, 0, 0)), ConfigFormat::Toml),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(
+                ConfigFormat::for_version(version),
+                expected,
+                "{:?}",
+                version
+            );
+        }
+    }
+}
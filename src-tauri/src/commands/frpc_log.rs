@@ -0,0 +1,349 @@
+use crate::models::{FrpcLogEvent, FrpcLogEventKind, FrpcLogLevel, TunnelKey};
+use tauri::Emitter;
+
+// frpc 日志行的解析结果，例如:
+// 2024/01/01 12:00:00 [I] [control.go:172] [0123456789abcdef] [ssh] start proxy success
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedLogLine {
+    pub level: Option<FrpcLogLevel>,
+    pub source: Option<String>,
+    pub run_id: Option<String>,
+    pub proxy_name: Option<String>,
+    pub content: String,
+}
+
+impl FrpcLogEventKind {
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            FrpcLogEventKind::LoginSuccess => "tunnel-connected",
+            FrpcLogEventKind::LoginFailed => "login-failed",
+            FrpcLogEventKind::ConnectFailed => "connect-failed",
+            FrpcLogEventKind::ProxyStarted => "proxy-started",
+            FrpcLogEventKind::ProxyStartFailed => "proxy-start-failed",
+            FrpcLogEventKind::Reconnecting => "tunnel-reconnecting",
+        }
+    }
+}
+
+fn parse_level(letter: &str) -> Option<FrpcLogLevel> {
+    match letter {
+        "T" => Some(FrpcLogLevel::Trace),
+        "D" => Some(FrpcLogLevel::Debug),
+        "I" => Some(FrpcLogLevel::Info),
+        "W" => Some(FrpcLogLevel::Warn),
+        "E" => Some(FrpcLogLevel::Error),
+        _ => None,
+    }
+}
+
+// 取出开头的 `[xxx]`，返回括号内文本和剩余部分
+fn take_bracket(text: &str) -> Option<(&str, &str)> {
+    let rest = text.trim_start().strip_prefix('[')?;
+    let end = rest.find(']')?;
+    Some((&rest[..end], &rest[end + 1..]))
+}
+
+// frpc 的 run id 是 16 位十六进制字符串
+fn is_run_id(text: &str) -> bool {
+    text.len() == 16 && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_source_location(text: &str) -> bool {
+    text.rsplit_once(':')
+        .map(|(file, line)| file.ends_with(".go") && line.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false)
+}
+
+pub fn parse_log_line(line: &str) -> Option<ParsedLogLine> {
+    let mut parsed = ParsedLogLine::default();
+    let mut rest = line;
+
+    // 跳过时间戳，定位到级别字母
+    loop {
+        let start = rest.find('[')?;
+        let (inner, after) = take_bracket(&rest[start..])?;
+        if let Some(level) = parse_level(inner) {
+            parsed.level = Some(level);
+            rest = after;
+            break;
+        }
+        rest = &rest[start + 1..];
+    }
+
+    if let Some((inner, after)) = take_bracket(rest) {
+        if is_source_location(inner) {
+            parsed.source = Some(inner.to_string());
+            rest = after;
+        }
+    }
+
+    let mut prefixes = Vec::new();
+    while let Some((inner, after)) = take_bracket(rest) {
+        if inner.is_empty() || inner.contains(' ') {
+            break;
+        }
+        prefixes.push(inner);
+        rest = after;
+    }
+
+    match prefixes.as_slice() {
+        [] => {}
+        [single] if is_run_id(single) => parsed.run_id = Some(single.to_string()),
+        [single] => parsed.proxy_name = Some(single.to_string()),
+        [run_id, proxy_name, ..] => {
+            parsed.run_id = Some(run_id.to_string());
+            parsed.proxy_name = Some(proxy_name.to_string());
+        }
+    }
+
+    parsed.content = rest.trim().to_string();
+    Some(parsed)
+}
+
+// `lower` 是 `content.to_ascii_lowercase()`，两者字节位置一致
+fn reason_after(content: &str, lower: &str, marker: &str) -> Option<String> {
+    let pos = lower.find(marker)?;
+    let reason = content[pos + marker.len()..]
+        .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+        .trim();
+    if reason.is_empty() {
+        None
+    } else {
+        Some(reason.to_string())
+    }
+}
+
+pub fn classify_log_line(parsed: &ParsedLogLine) -> Option<(FrpcLogEventKind, Option<String>)> {
+    let lower = parsed.content.to_ascii_lowercase();
+
+    let (kind, marker) = if lower.contains("login to server success")
+        || lower.contains("login to the server success")
+    {
+        (FrpcLogEventKind::LoginSuccess, None)
+    } else if lower.contains("start proxy success") {
+        (FrpcLogEventKind::ProxyStarted, None)
+    } else if lower.contains("start error") {
+        (FrpcLogEventKind::ProxyStartFailed, Some("start error"))
+    } else if lower.contains("login to server failed") {
+        (
+            FrpcLogEventKind::LoginFailed,
+            Some("login to server failed"),
+        )
+    } else if lower.contains("login to the server failed") {
+        (
+            FrpcLogEventKind::LoginFailed,
+            Some("login to the server failed"),
+        )
+    } else if lower.contains("connect to server error") {
+        (
+            FrpcLogEventKind::ConnectFailed,
+            Some("connect to server error"),
+        )
+    } else if lower.contains("try to reconnect") || lower.contains("reconnect to server") {
+        (FrpcLogEventKind::Reconnecting, None)
+    } else {
+        return None;
+    };
+
+    let reason = marker.and_then(|m| reason_after(&parsed.content, &lower, m));
+    Some((kind, reason))
+}
+
+fn extract_login_run_id(content: &str) -> Option<String> {
+    let pos = content.find("run id")?;
+    take_bracket(&content[pos + "run id".len()..]).map(|(id, _)| id.to_string())
+}
+
+pub fn parse_log_event(
+    tunnel_key: &TunnelKey,
+    line: &str,
+    timestamp: &str,
+) -> Option<FrpcLogEvent> {
+    let parsed = parse_log_line(line)?;
+    let (kind, reason) = classify_log_line(&parsed)?;
+
+    let run_id = if kind == FrpcLogEventKind::LoginSuccess {
+        extract_login_run_id(&parsed.content).or(parsed.run_id)
+    } else {
+        parsed.run_id
+    };
+
+    Some(FrpcLogEvent {
+        tunnel_key: tunnel_key.clone(),
+        kind,
+        level: parsed.level,
+        source: parsed.source,
+        run_id,
+        proxy_name: parsed.proxy_name,
+        reason,
+        message: parsed.content,
+        timestamp: timestamp.to_string(),
+    })
+}
+
+pub fn emit_log_event(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    line: &str,
+    timestamp: &str,
+) -> Option<FrpcLogEvent> {
+    let event = parse_log_event(tunnel_key, line, timestamp)?;
+    let _ = app_handle.emit(event.kind.event_name(), event.clone());
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(
+        level: FrpcLogLevel,
+        source: Option<&str>,
+        run_id: Option<&str>,
+        proxy_name: Option<&str>,
+        content: &str,
+    ) -> ParsedLogLine {
+        ParsedLogLine {
+            level: Some(level),
+            source: source.map(str::to_string),
+            run_id: run_id.map(str::to_string),
+            proxy_name: proxy_name.map(str::to_string),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_lines() {
+        let cases = [
+            (
+                "2024/01/01 12:00:00 [I] [control.go:172] [0123456789abcdef] [ssh] start proxy success",
+                Some(parsed(
+                    FrpcLogLevel::Info,
+                    Some("control.go:172"),
+                    Some("0123456789abcdef"),
+                    Some("ssh"),
+                    "start proxy success",
+                )),
+            ),
+            (
+                "2024-01-01 12:00:00.000 [W] [client/service.go:295] [0123456789abcdef] try to reconnect",
+                Some(parsed(
+                    FrpcLogLevel::Warn,
+                    Some("client/service.go:295"),
+                    Some("0123456789abcdef"),
+                    None,
+                    "try to reconnect",
+                )),
+            ),
+            (
+                "[E] [web] start error: port already used",
+                Some(parsed(
+                    FrpcLogLevel::Error,
+                    None,
+                    None,
+                    Some("web"),
+                    "start error: port already used",
+                )),
+            ),
+            (
+                "[D] [a.go:1] [0123456789abcdef] [web] [extra] message",
+                Some(parsed(
+                    FrpcLogLevel::Debug,
+                    Some("a.go:1"),
+                    Some("0123456789abcdef"),
+                    Some("web"),
+                    "message",
+                )),
+            ),
+            (
+                "[I] [deadbeef] start proxy success",
+                Some(parsed(
+                    FrpcLogLevel::Info,
+                    None,
+                    None,
+                    Some("deadbeef"),
+                    "start proxy success",
+                )),
+            ),
+            (
+                "[E] [proxy.go:1] [12345678] start error: port already used",
+                Some(parsed(
+                    FrpcLogLevel::Error,
+                    Some("proxy.go:1"),
+                    None,
+                    Some("12345678"),
+                    "start error: port already used",
+                )),
+            ),
+            (
+                "[T] [not a prefix] text",
+                Some(parsed(FrpcLogLevel::Trace, None, None, None, "[not a prefix] text")),
+            ),
+            ("[I]", Some(parsed(FrpcLogLevel::Info, None, None, None, ""))),
+            ("plain text without level", None),
+            ("", None),
+            ("[X] unknown level", None),
+            ("[I unterminated", None),
+            ("2024/01/01 [", None),
+        ];
+        for (line, expected) in cases {
+            assert_eq!(parse_log_line(line), expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn classify_lines() {
+        let cases = [
+            (
+                "[I] [service.go:1] [0123456789abcdef] login to server success, get run id [0123456789abcdef]",
+                Some((FrpcLogEventKind::LoginSuccess, None)),
+            ),
+            (
+                "[W] [service.go:1] login to the server failed: i/o timeout",
+                Some((FrpcLogEventKind::LoginFailed, Some("i/o timeout"))),
+            ),
+            (
+                "[W] [service.go:1] login to server failed:",
+                Some((FrpcLogEventKind::LoginFailed, None)),
+            ),
+            (
+                "[E] [web] start error: port already used",
+                Some((FrpcLogEventKind::ProxyStartFailed, Some("port already used"))),
+            ),
+            (
+                "[W] connect to server error: connection refused",
+                Some((FrpcLogEventKind::ConnectFailed, Some("connection refused"))),
+            ),
+            (
+                "[I] [ssh] start proxy success",
+                Some((FrpcLogEventKind::ProxyStarted, None)),
+            ),
+            (
+                "[I] try to reconnect",
+                Some((FrpcLogEventKind::Reconnecting, None)),
+            ),
+            ("[I] nothing interesting", None),
+        ];
+        for (line, expected) in cases {
+            let parsed = parse_log_line(line).unwrap();
+            let classified = classify_log_line(&parsed)
+                .map(|(kind, reason)| (kind, reason.as_deref().map(str::to_string)));
+            let expected = expected.map(|(kind, reason)| (kind, reason.map(str::to_string)));
+            assert_eq!(classified, expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn login_event_run_id() {
+        let key = TunnelKey::Custom("my_tunnel".to_string());
+        let event = parse_log_event(
+            &key,
+            "[I] [service.go:1] login to server success, get run id [fedcba9876543210]",
+            "2024/01/01 12:00:00",
+        )
+        .unwrap();
+        assert_eq!(event.kind, FrpcLogEventKind::LoginSuccess);
+        assert_eq!(event.run_id.as_deref(), Some("fedcba9876543210"));
+        assert_eq!(event.tunnel_key, key);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::commands::tunnel_status;
+use crate::models::{LogMessage, ProcessGuardState, TunnelKey};
+use crate::utils::sanitize_log;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+// frpc 的输出写入文件而不是管道，启动器退出后 frpc 不会因为管道关闭而被 SIGPIPE 结束
+const OUTPUT_DIR: &str = "frpc_output";
+const TAIL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn output_paths(app_dir: &Path, tunnel_key: &TunnelKey) -> [(PathBuf, bool); 2] {
+    let dir = app_dir.join(OUTPUT_DIR);
+    [
+        (dir.join(format!("tunnel_{}.out", tunnel_key)), false),
+        (dir.join(format!("tunnel_{}.err", tunnel_key)), true),
+    ]
+}
+
+fn create_output_file(path: &Path) -> Result<File, String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(path)
+        .map_err(|e| format!("创建 frpc 输出文件失败: {}", e))
+}
+
+// 创建新的输出文件，返回 frpc 的 stdout 和 stderr
+pub fn create_output(app_dir: &Path, tunnel_key: &TunnelKey) -> Result<(Stdio, Stdio), String> {
+    std::fs::create_dir_all(app_dir.join(OUTPUT_DIR))
+        .map_err(|e| format!("创建 frpc 输出目录失败: {}", e))?;
+    let [(stdout, _), (stderr, _)] = output_paths(app_dir, tunnel_key);
+    Ok((
+        create_output_file(&stdout)?.into(),
+        create_output_file(&stderr)?.into(),
+    ))
+}
+
+/// 接管进程前确认输出文件存在，否则进程仍在向已关闭的管道输出
+pub fn has_output(app_dir: &Path, tunnel_key: &TunnelKey) -> bool {
+    output_paths(app_dir, tunnel_key)
+        .iter()
+        .all(|(path, _)| path.exists())
+}
+
+// 持续读取 frpc 写入的输出文件，读到末尾时等待新的输出，收到停止通知并读完后结束
+struct TailReader {
+    file: File,
+    stop: Arc<AtomicBool>,
+}
+
+impl Read for TailReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            // 先读取停止标记，保证进程退出前写入的内容都能读到
+            let stopped = self.stop.load(Ordering::SeqCst);
+            let n = self.file.read(buf)?;
+            if n > 0 || stopped {
+                return Ok(n);
+            }
+            thread::sleep(TAIL_INTERVAL);
+        }
+    }
+}
+
+// 隧道的日志读取线程
+pub struct OutputTail {
+    stop: Arc<AtomicBool>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+impl OutputTail {
+    pub fn stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop.clone()
+    }
+
+    // 进程退出后调用，等待读取线程输出剩余日志
+    pub fn finish(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for reader in self.readers {
+            let _ = reader.join();
+        }
+    }
+}
+
+fn spawn_log_reader(
+    app_handle: tauri::AppHandle,
+    tunnel_key: TunnelKey,
+    secrets: Arc<Vec<String>>,
+    reader: TailReader,
+    is_stderr: bool,
+) -> Option<JoinHandle<()>> {
+    let thread_name = if is_stderr {
+        format!("frpc-stderr-{}", tunnel_key)
+    } else {
+        format!("frpc-stdout-{}", tunnel_key)
+    };
+
+    let result = thread::Builder::new().name(thread_name).spawn(move || {
+        let reader = BufReader::new(reader);
+        for line in reader.lines().map_while(Result::ok) {
+            let secret_refs: Vec<&str> = secrets.iter().map(String::as_str).collect();
+            let clean_line = strip_ansi_escapes::strip_str(&line);
+            let sanitized_line = sanitize_log(&clean_line, &secret_refs);
+            let timestamp = chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string();
+
+            let log_event = crate::commands::frpc_log::emit_log_event(
+                &app_handle,
+                &tunnel_key,
+                &sanitized_line,
+                &timestamp,
+            );
+            tunnel_status::apply_log_line(&app_handle, &tunnel_key, log_event.as_ref());
+
+            let guard_state = app_handle.state::<ProcessGuardState>();
+            let _ = tauri::async_runtime::block_on(async {
+                crate::commands::process_guard::check_log_and_stop_guard(
+                    app_handle.clone(),
+                    tunnel_key.clone(),
+                    sanitized_line.clone(),
+                    guard_state,
+                )
+                .await
+            });
+
+            let message = if is_stderr {
+                format!("[ERR] {}", sanitized_line)
+            } else {
+                sanitized_line
+            };
+
+            crate::commands::tunnel_log::append_line(
+                &app_handle,
+                &tunnel_key,
+                &timestamp,
+                &message,
+            );
+
+            let _ = app_handle.emit(
+                "frpc-log",
+                LogMessage {
+                    tunnel_key: tunnel_key.clone(),
+                    message,
+                    timestamp,
+                },
+            );
+        }
+    });
+
+    match result {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            eprintln!(
+                "[错误] 创建 {} 监听线程失败: {}",
+                if is_stderr { "stderr" } else { "stdout" },
+                e
+            );
+            None
+        }
+    }
+}
+
+// 读取隧道的 frpc 输出并转发为日志，接管的进程从文件末尾开始读取，避免重复输出旧日志
+pub fn spawn_log_readers(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    tunnel_key: &TunnelKey,
+    secrets: Vec<String>,
+    from_end: bool,
+) -> OutputTail {
+    let stop = Arc::new(AtomicBool::new(false));
+    let secrets = Arc::new(secrets);
+    let mut readers = Vec::new();
+
+    for (path, is_stderr) in output_paths(app_dir, tunnel_key) {
+        let file = File::open(&path).and_then(|mut file| {
+            if from_end {
+                file.seek(SeekFrom::End(0))?;
+            }
+            Ok(file)
+        });
+        let file = match file {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("[错误] 打开 frpc 输出文件 {:?} 失败: {}", path, e);
+                continue;
+            }
+        };
+
+        let reader = TailReader {
+            file,
+            stop: stop.clone(),
+        };
+        readers.extend(spawn_log_reader(
+            app_handle.clone(),
+            tunnel_key.clone(),
+            secrets.clone(),
+            reader,
+            is_stderr,
+        ));
+    }
+
+    OutputTail { stop, readers }
+}
@@ -0,0 +1,220 @@
+use crate::commands::frpc_config::FrpcVersion;
+use crate::commands::settings;
+use crate::models::{FrpcVersionPins, InstalledFrpcVersion, TunnelKey};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+// 各版本的 frpc 保存在 frpc_versions/<版本>/ 下
+const VERSIONS_DIR: &str = "frpc_versions";
+
+fn get_app_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用目录失败: {}", e))
+}
+
+pub fn frpc_file_name() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "frpc.exe"
+    } else {
+        "frpc"
+    }
+}
+
+/// 未固定版本的隧道使用的 frpc
+pub fn default_frpc_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(frpc_file_name())
+}
+
+pub fn version_frpc_path(app_dir: &Path, version: &str) -> PathBuf {
+    app_dir
+        .join(VERSIONS_DIR)
+        .join(version)
+        .join(frpc_file_name())
+}
+
+/// 校验并规范化版本号，版本号会作为目录名使用
+pub fn normalize_version(version: &str) -> Result<String, String> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let valid = version.len() <= 64
+        && version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+'));
+    if !valid {
+        return Err(format!("无效的 frpc 版本号: {}", version));
+    }
+    Ok(version.to_string())
+}
+
+// 已安装的版本，新版本在前
+fn installed_versions(app_dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(app_dir.join(VERSIONS_DIR)) else {
+        return Vec::new();
+    };
+
+    let mut versions: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|version| normalize_version(version).as_deref() == Ok(version.as_str()))
+        .filter(|version| version_frpc_path(app_dir, version).is_file())
+        .collect();
+    versions.sort_by_cached_key(|version| (FrpcVersion::parse(version), version.clone()));
+    versions.reverse();
+    versions
+}
+
+fn tunnel_frpc_path(pins: &FrpcVersionPins, app_dir: &Path, tunnel_key: &TunnelKey) -> PathBuf {
+    match pins.pins.get(tunnel_key) {
+        Some(version) => version_frpc_path(app_dir, version),
+        None => default_frpc_path(app_dir),
+    }
+}
+
+/// 按当前的版本设置找出使用指定 frpc 的隧道
+pub fn tunnels_using(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    tunnel_keys: Vec<TunnelKey>,
+    frpc_path: &Path,
+) -> Result<Vec<TunnelKey>, String> {
+    let pins = settings::load::<FrpcVersionPins>(app_handle)?;
+    Ok(tunnel_keys
+        .into_iter()
+        .filter(|key| tunnel_frpc_path(&pins, app_dir, key) == frpc_path)
+        .collect())
+}
+
+/// 启动隧道时使用的 frpc，隧道固定了版本时使用对应版本
+pub fn resolve_frpc_path(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    tunnel_key: &TunnelKey,
+) -> Result<PathBuf, String> {
+    let pins = settings::load::<FrpcVersionPins>(app_handle)?;
+    let frpc_path = tunnel_frpc_path(&pins, app_dir, tunnel_key);
+    let Some(version) = pins.pins.get(tunnel_key) else {
+        if !frpc_path.exists() {
+            return Err("frpc 未找到，请先下载".to_string());
+        }
+        return Ok(frpc_path);
+    };
+
+    if !frpc_path.exists() {
+        return Err(format!(
+            "隧道固定使用的 frpc {} 未安装，请先下载该版本或取消固定",
+            version
+        ));
+    }
+    Ok(frpc_path)
+}
+
+/// 隧道被删除时移除其固定的版本
+pub fn remove_pin(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey) {
+    let result = settings::update(app_handle, |store: &mut FrpcVersionPins| {
+        store.pins.remove(tunnel_key);
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("移除隧道 {} 的 frpc 版本设置失败: {}", tunnel_key, e);
+    }
+}
+
+#[tauri::command]
+pub async fn list_frpc_versions(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<InstalledFrpcVersion>, String> {
+    let app_dir = get_app_dir(&app_handle)?;
+    let pins = settings::load::<FrpcVersionPins>(&app_handle)?;
+
+    Ok(installed_versions(&app_dir)
+        .into_iter()
+        .map(|version| InstalledFrpcVersion {
+            path: version_frpc_path(&app_dir, &version)
+                .to_string_lossy()
+                .to_string(),
+            tunnels: pins
+                .pins
+                .iter()
+                .filter(|(_, pinned)| **pinned == version)
+                .map(|(key, _)| key.clone())
+                .collect(),
+            version,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn get_tunnel_frpc_version(
+    app_handle: tauri::AppHandle,
+    tunnel_key: TunnelKey,
+) -> Result<Option<String>, String> {
+    let mut pins = settings::load::<FrpcVersionPins>(&app_handle)?;
+    Ok(pins.pins.remove(&tunnel_key))
+}
+
+/// 固定隧道使用的 frpc 版本，version 为空时使用默认的 frpc
+#[tauri::command]
+pub async fn set_tunnel_frpc_version(
+    app_handle: tauri::AppHandle,
+    tunnel_key: TunnelKey,
+    version: Option<String>,
+) -> Result<(), String> {
+    let version = match version {
+        Some(version) => {
+            let version = normalize_version(&version)?;
+            let app_dir = get_app_dir(&app_handle)?;
+            if !version_frpc_path(&app_dir, &version).exists() {
+                return Err(format!("frpc {} 未安装，请先下载该版本", version));
+            }
+            Some(version)
+        }
+        None => None,
+    };
+
+    settings::update(&app_handle, |store: &mut FrpcVersionPins| {
+        match version {
+            Some(version) => store.pins.insert(tunnel_key, version),
+            None => store.pins.remove(&tunnel_key),
+        };
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_version;
+
+    #[test]
+    fn normalize_accepts_release_names() {
+        assert_eq!(normalize_version("0.51.2").as_deref(), Ok("0.51.2"));
+        assert_eq!(normalize_version(" v0.58.1\n").as_deref(), Ok("0.58.1"));
+        assert_eq!(
+            normalize_version("0.51.2_chmlfrp").as_deref(),
+            Ok("0.51.2_chmlfrp")
+        );
+        assert_eq!(
+            normalize_version("1.0.0-rc.1+build").as_deref(),
+            Ok("1.0.0-rc.1+build")
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_path_like_names() {
+        for raw in [
+            "",
+            "v",
+            "latest",
+            "../0.51.2",
+            "0.51/2",
+            "0.51 2",
+            "0.51\\2",
+        ] {
+            assert!(normalize_version(raw).is_err(), "{:?}", raw);
+        }
+        assert!(normalize_version(&"1".repeat(65)).is_err());
+        assert!(normalize_version(&"1".repeat(64)).is_ok());
+    }
+}
@@ -0,0 +1,216 @@
+use crate::commands::settings;
+use crate::models::{CompiledGuardRule, GuardRule, GuardRuleAction, GuardRules};
+use regex::RegexBuilder;
+use tauri::{Manager, State};
+
+// 内置的停止守护规则，id 固定不变，用于与规则文件中的规则合并
+const BUILTIN_RULES: &[(&str, &str)] = &[
+    (
+        "builtin-token-mismatch",
+        "token in login doesn't match token from configuration",
+    ),
+    ("builtin-authorization-failed", "authorization failed"),
+    ("builtin-invalid-token", "invalid token"),
+    ("builtin-connection-reset", "read: connection reset by peer"),
+    ("builtin-user-not-found", "错误的用户token，此用户不存在"),
+    (
+        "builtin-tunnel-limit",
+        "允许的隧道数量超出上限，请删除隧道或续费vip",
+    ),
+    ("builtin-not-owner", "不属于你"),
+    ("builtin-missing-params", "缺少用户token或隧道id参数"),
+    ("builtin-free-plan", "您目前为免费会员"),
+    (
+        "builtin-config-mismatch",
+        "客户端代理参数错误，配置文件与记录不匹配。请不要随意修改配置文件！",
+    ),
+    ("builtin-api-error", "ChmlFrp API Error"),
+];
+
+pub fn default_rules() -> Vec<GuardRule> {
+    BUILTIN_RULES
+        .iter()
+        .map(|(id, pattern)| GuardRule {
+            id: id.to_string(),
+            pattern: pattern.to_string(),
+            regex: false,
+            case_sensitive: false,
+            action: GuardRuleAction::StopGuard,
+            builtin: true,
+        })
+        .collect()
+}
+
+fn compile_rule(rule: GuardRule) -> Result<CompiledGuardRule, String> {
+    let pattern = if rule.regex {
+        rule.pattern.clone()
+    } else {
+        regex::escape(&rule.pattern)
+    };
+
+    let matcher = RegexBuilder::new(&pattern)
+        .case_insensitive(!rule.case_sensitive)
+        .build()
+        .map_err(|e| format!("规则 \"{}\" 不是有效的正则表达式: {}", rule.pattern, e))?;
+
+    Ok(CompiledGuardRule { rule, matcher })
+}
+
+fn save_rules(app_handle: &tauri::AppHandle, rules: &[CompiledGuardRule]) -> Result<(), String> {
+    let list: Vec<GuardRule> = rules.iter().map(|c| c.rule.clone()).collect();
+    settings::save(app_handle, &list).map_err(|e| format!("保存守护规则失败: {}", e))
+}
+
+// 补上规则文件中缺少的内置规则，新版本增加的内置规则也会生效
+fn merge_builtin_rules(mut rules: Vec<GuardRule>) -> Vec<GuardRule> {
+    for builtin in default_rules() {
+        if !rules.iter().any(|rule| rule.id == builtin.id) {
+            rules.push(builtin);
+        }
+    }
+    rules
+}
+
+// 启动时加载守护规则并按 id 合并内置规则，文件不存在或无法读取时只使用内置规则
+pub fn load_guard_rules(app_handle: &tauri::AppHandle) {
+    let rules = match settings::load::<Vec<GuardRule>>(app_handle) {
+        Ok(rules) => merge_builtin_rules(rules),
+        Err(e) => {
+            eprintln!("[守护进程] 读取守护规则失败，使用内置规则: {}", e);
+            default_rules()
+        }
+    };
+
+    let compiled: Vec<CompiledGuardRule> = rules
+        .into_iter()
+        .filter_map(|rule| match compile_rule(rule) {
+            Ok(compiled) => Some(compiled),
+            Err(e) => {
+                eprintln!("[守护进程] {}", e);
+                None
+            }
+        })
+        .collect();
+
+    let guard_rules = app_handle.state::<GuardRules>();
+    let mut rules = match guard_rules.rules.lock() {
+        Ok(rules) => rules,
+        Err(_) => return,
+    };
+    *rules = compiled;
+}
+
+// 返回第一条匹配日志的规则
+pub fn match_rule(guard_rules: &GuardRules, message: &str) -> Option<GuardRule> {
+    let rules = guard_rules.rules.lock().ok()?;
+    rules
+        .iter()
+        .find(|c| c.matcher.is_match(message))
+        .map(|c| c.rule.clone())
+}
+
+#[tauri::command]
+pub async fn list_guard_rules(
+    guard_rules: State<'_, GuardRules>,
+) -> Result<Vec<GuardRule>, String> {
+    let rules = guard_rules
+        .rules
+        .lock()
+        .map_err(|e| format!("获取守护规则锁失败: {}", e))?;
+    Ok(rules.iter().map(|c| c.rule.clone()).collect())
+}
+
+// 用户规则的 id，同一毫秒内添加多条规则时追加序号
+fn new_rule_id(rules: &[CompiledGuardRule]) -> String {
+    let base = format!("rule-{}", chrono::Local::now().timestamp_millis());
+    let mut id = base.clone();
+    let mut suffix = 1;
+    while rules.iter().any(|c| c.rule.id == id) {
+        suffix += 1;
+        id = format!("{}-{}", base, suffix);
+    }
+    id
+}
+
+#[tauri::command]
+pub async fn add_guard_rule(
+    app_handle: tauri::AppHandle,
+    pattern: String,
+    regex: bool,
+    case_sensitive: bool,
+    action: GuardRuleAction,
+    guard_rules: State<'_, GuardRules>,
+) -> Result<GuardRule, String> {
+    if pattern.trim().is_empty() {
+        return Err("规则内容不能为空".to_string());
+    }
+
+    let mut compiled = compile_rule(GuardRule {
+        id: String::new(),
+        pattern,
+        regex,
+        case_sensitive,
+        action,
+        builtin: false,
+    })?;
+
+    let mut rules = guard_rules
+        .rules
+        .lock()
+        .map_err(|e| format!("获取守护规则锁失败: {}", e))?;
+    compiled.rule.id = new_rule_id(&rules);
+    let rule = compiled.rule.clone();
+    rules.push(compiled);
+    save_rules(&app_handle, &rules)?;
+
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn remove_guard_rule(
+    app_handle: tauri::AppHandle,
+    rule_id: String,
+    guard_rules: State<'_, GuardRules>,
+) -> Result<(), String> {
+    let mut rules = guard_rules
+        .rules
+        .lock()
+        .map_err(|e| format!("获取守护规则锁失败: {}", e))?;
+
+    let index = rules
+        .iter()
+        .position(|c| c.rule.id == rule_id)
+        .ok_or_else(|| "守护规则不存在".to_string())?;
+    // 内置规则在下次启动时会被重新加入，因此不允许删除
+    if rules[index].rule.builtin {
+        return Err("内置守护规则不能删除".to_string());
+    }
+    rules.remove(index);
+
+    save_rules(&app_handle, &rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn builtin_rule_ids_are_unique() {
+        let ids: HashSet<&str> = BUILTIN_RULES.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids.len(), BUILTIN_RULES.len());
+    }
+
+    #[test]
+    fn new_rule_ids_do_not_collide() {
+        let mut rules: Vec<CompiledGuardRule> = Vec::new();
+        for _ in 0..3 {
+            let mut rule = default_rules().remove(0);
+            rule.id = new_rule_id(&rules);
+            rule.builtin = false;
+            rules.push(compile_rule(rule).unwrap());
+        }
+        let ids: HashSet<&str> = rules.iter().map(|c| c.rule.id.as_str()).collect();
+        assert_eq!(ids.len(), 3);
+    }
+}
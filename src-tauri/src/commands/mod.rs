@@ -1,13 +1,33 @@
 // 命令模块
 pub mod autostart;
 pub mod background;
+pub mod batch;
+pub mod crypto;
 pub mod custom_tunnel;
 pub mod download;
+pub mod download_job;
+pub mod download_mirror;
+pub mod frpc_archive;
+pub mod frpc_config;
+pub mod frpc_log;
+pub mod frpc_output;
+pub mod frpc_version;
+pub mod guard_rules;
 pub mod http;
 pub mod ping;
 pub mod process;
+pub mod process_adopt;
+pub mod process_exit;
 pub mod process_guard;
+pub mod process_stop;
+pub mod profile;
+pub mod settings;
+pub mod shutdown;
+pub mod token_vault;
 pub mod tray;
+pub mod tunnel_config_cache;
+pub mod tunnel_log;
+pub mod tunnel_status;
 
 // 重新导出所有命令函数，方便使用
 pub use autostart::*;
@@ -0,0 +1,410 @@
+use crate::commands::download::compute_sha256;
+use crate::commands::frpc_output::{self, OutputTail};
+use crate::commands::{process_exit, token_vault, tunnel_status};
+use crate::models::{
+    AdoptedProcess, FrpcProcesses, LogMessage, TunnelKey, TunnelPidRecord, TunnelStatus,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+use tauri::{Emitter, Manager};
+
+const PID_DIR: &str = "pids";
+const ADOPTED_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const ORPHAN_TERMINATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// frpc 的哈希按路径缓存，文件大小和修改时间不变时复用
+type BinaryHashCache = HashMap<PathBuf, (u64, SystemTime, String)>;
+static BINARY_HASHES: Mutex<Option<BinaryHashCache>> = Mutex::new(None);
+
+fn get_timestamp() -> String {
+    chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string()
+}
+
+fn get_pid_dir(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .ok()
+        .map(|dir| dir.join(PID_DIR))
+}
+
+fn get_pid_file(pid_dir: &Path, tunnel_key: &TunnelKey) -> PathBuf {
+    pid_dir.join(format!("tunnel_{}.json", tunnel_key))
+}
+
+fn binary_hash(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?;
+    let mut cache = BINARY_HASHES.lock().ok()?;
+    let cache = cache.get_or_insert_with(HashMap::new);
+    if let Some((len, cached_modified, hash)) = cache.get(path) {
+        if *len == metadata.len() && *cached_modified == modified {
+            return Some(hash.clone());
+        }
+    }
+
+    let hash = compute_sha256(path).ok()?;
+    cache.insert(path.to_path_buf(), (metadata.len(), modified, hash.clone()));
+    Some(hash)
+}
+
+// 查询进程的启动时间和可执行文件路径，进程不存在时返回 None
+fn query_process(pid: u32) -> Option<(u64, Option<PathBuf>)> {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::OnlyIfNotSet),
+    );
+    system
+        .process(pid)
+        .map(|process| (process.start_time(), process.exe().map(Path::to_path_buf)))
+}
+
+// 通过启动时间确认 PID 未被其他进程复用
+pub fn is_process_alive(pid: u32, start_time: u64) -> bool {
+    query_process(pid).is_some_and(|(started, _)| started == start_time)
+}
+
+fn request_terminate(pid: u32) -> bool {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system
+        .process(pid)
+        .and_then(|process| process.kill_with(Signal::Term))
+        .unwrap_or(false)
+}
+
+// 同时请求多个进程退出并共用同一个超时时间，超时后强制结束，返回是否全部正常退出
+pub fn terminate_pids(targets: &[(u32, u64)], grace: Duration) -> bool {
+    let (mut waiting, mut forced): (Vec<_>, Vec<_>) = targets
+        .iter()
+        .copied()
+        .filter(|&(pid, start_time)| is_process_alive(pid, start_time))
+        .partition(|&(pid, _)| !grace.is_zero() && request_terminate(pid));
+
+    let deadline = Instant::now() + grace;
+    while !waiting.is_empty() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+        waiting.retain(|&(pid, start_time)| is_process_alive(pid, start_time));
+    }
+
+    let graceful = waiting.is_empty() && forced.is_empty();
+    forced.append(&mut waiting);
+    for (pid, _) in forced {
+        process_exit::kill_pid(pid);
+    }
+    graceful
+}
+
+// 请求进程退出，超时后强制结束，返回是否为正常退出
+pub fn terminate_pid(pid: u32, start_time: u64, grace: Duration) -> bool {
+    terminate_pids(&[(pid, start_time)], grace)
+}
+
+// 启动 frpc 后记录进程信息，启动器异常退出后可据此接管进程
+pub fn write_pid_file(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    pid: u32,
+    config_path: &Path,
+    frpc_path: &Path,
+) {
+    let Some(pid_dir) = get_pid_dir(app_handle) else {
+        return;
+    };
+    if std::fs::create_dir_all(&pid_dir).is_err() {
+        return;
+    }
+
+    let Some((start_time, _)) = query_process(pid) else {
+        return;
+    };
+
+    let record = TunnelPidRecord {
+        tunnel_key: tunnel_key.clone(),
+        pid,
+        start_time,
+        config_path: config_path.to_string_lossy().to_string(),
+        binary_hash: binary_hash(frpc_path).unwrap_or_default(),
+    };
+
+    if let Ok(content) = serde_json::to_string_pretty(&record) {
+        if let Err(e) = std::fs::write(get_pid_file(&pid_dir, tunnel_key), content) {
+            eprintln!("[进程管理] 写入隧道 {} 的 PID 文件失败: {}", tunnel_key, e);
+        }
+    }
+}
+
+// 仅当记录的 PID 与退出的进程一致时删除，避免误删新进程的记录
+pub fn remove_pid_file(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey, pid: u32) {
+    let Some(pid_dir) = get_pid_dir(app_handle) else {
+        return;
+    };
+    let pid_file = get_pid_file(&pid_dir, tunnel_key);
+
+    let matches = std::fs::read_to_string(&pid_file)
+        .ok()
+        .and_then(|content| serde_json::from_str::<TunnelPidRecord>(&content).ok())
+        .map_or(true, |record| record.pid == pid);
+    if matches {
+        let _ = std::fs::remove_file(pid_file);
+    }
+}
+
+fn has_process(processes: &FrpcProcesses, tunnel_key: &TunnelKey) -> bool {
+    let has_child = processes
+        .processes
+        .lock()
+        .map(|procs| procs.contains_key(tunnel_key))
+        .unwrap_or(false);
+    has_child
+        || processes
+            .adopted
+            .lock()
+            .map(|adopted| adopted.contains_key(tunnel_key))
+            .unwrap_or(false)
+}
+
+/// 隧道是否正在运行，正在启动的隧道也视为运行中
+pub fn is_running(processes: &FrpcProcesses, tunnel_key: &TunnelKey) -> bool {
+    let starting = processes
+        .starting
+        .lock()
+        .map(|starting| starting.contains(tunnel_key))
+        .unwrap_or(false);
+    starting || has_process(processes, tunnel_key)
+}
+
+/// 启动期间占用隧道，释放前同一隧道的其他启动会失败
+pub struct StartReservation<'a> {
+    processes: &'a FrpcProcesses,
+    tunnel_key: TunnelKey,
+}
+
+impl Drop for StartReservation<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut starting) = self.processes.starting.lock() {
+            starting.remove(&self.tunnel_key);
+        }
+    }
+}
+
+/// 检查隧道未在运行并占用它，进程记录到 processes 前应一直持有
+pub fn reserve_start<'a>(
+    processes: &'a FrpcProcesses,
+    tunnel_key: &TunnelKey,
+) -> Result<StartReservation<'a>, String> {
+    let mut starting = processes
+        .starting
+        .lock()
+        .map_err(|e| format!("获取进程锁失败: {}", e))?;
+    if starting.contains(tunnel_key) || has_process(processes, tunnel_key) {
+        return Err("该隧道已在运行中".to_string());
+    }
+    starting.insert(tunnel_key.clone());
+    Ok(StartReservation {
+        processes,
+        tunnel_key: tunnel_key.clone(),
+    })
+}
+
+/// 接管的进程正在使用的配置文件，启动时清理生成的配置需跳过这些文件
+pub fn adopted_config_paths(processes: &FrpcProcesses) -> HashSet<PathBuf> {
+    processes
+        .adopted
+        .lock()
+        .map(|adopted| {
+            adopted
+                .values()
+                .map(|process| PathBuf::from(&process.config_path))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 所有运行中的隧道，包括接管的进程
+pub fn running_keys(processes: &FrpcProcesses) -> Result<Vec<TunnelKey>, String> {
+    let mut tunnel_keys: Vec<TunnelKey> = processes
+        .processes
+        .lock()
+        .map_err(|e| format!("获取进程锁失败: {}", e))?
+        .keys()
+        .cloned()
+        .collect();
+    tunnel_keys.extend(
+        processes
+            .adopted
+            .lock()
+            .map_err(|e| format!("获取进程锁失败: {}", e))?
+            .keys()
+            .cloned(),
+    );
+    Ok(tunnel_keys)
+}
+
+pub fn take_adopted(processes: &FrpcProcesses, tunnel_key: &TunnelKey) -> Option<AdoptedProcess> {
+    processes
+        .adopted
+        .lock()
+        .ok()
+        .and_then(|mut adopted| adopted.remove(tunnel_key))
+}
+
+// 接管的进程不是本进程的子进程，无法 wait，只能定期检查是否存活
+fn spawn_adopted_watcher(
+    app_handle: tauri::AppHandle,
+    tunnel_key: TunnelKey,
+    adopted: AdoptedProcess,
+    output: OutputTail,
+) {
+    let output_stop = output.stop_flag();
+    if let Err(e) = thread::Builder::new()
+        .name(format!("frpc-adopted-{}", tunnel_key))
+        .spawn(move || loop {
+            thread::sleep(ADOPTED_CHECK_INTERVAL);
+
+            let still_adopted = app_handle
+                .state::<FrpcProcesses>()
+                .adopted
+                .lock()
+                .map(|map| map.get(&tunnel_key).is_some_and(|p| p.pid == adopted.pid))
+                .unwrap_or(false);
+            if !still_adopted {
+                output.finish();
+                return;
+            }
+
+            if is_process_alive(adopted.pid, adopted.start_time) {
+                continue;
+            }
+
+            output.finish();
+            take_adopted(&app_handle.state::<FrpcProcesses>(), &tunnel_key);
+            process_exit::record_exit(&app_handle, &tunnel_key, adopted.pid, None, None);
+            return;
+        })
+    {
+        output_stop.store(true, Ordering::SeqCst);
+        eprintln!("[错误] 创建接管进程监听线程失败: {}", e);
+    }
+}
+
+enum Reconciled {
+    Adopted,
+    // 仍在运行但无法接管的进程
+    Orphan(u32, u64),
+    Gone,
+}
+
+fn reconcile_record(
+    app_handle: &tauri::AppHandle,
+    app_dir: &Path,
+    record: &TunnelPidRecord,
+) -> Reconciled {
+    let Some((start_time, exe)) = query_process(record.pid) else {
+        return Reconciled::Gone;
+    };
+    if start_time != record.start_time {
+        return Reconciled::Gone;
+    }
+
+    // 配置文件已被清理、frpc 已被替换或输出文件丢失时，无法安全接管，直接结束进程
+    let config_path = Path::new(&record.config_path);
+    let binary_matches = exe
+        .and_then(|exe| binary_hash(&exe))
+        .is_some_and(|hash| hash.eq_ignore_ascii_case(&record.binary_hash));
+    if !config_path.exists()
+        || !binary_matches
+        || !frpc_output::has_output(app_dir, &record.tunnel_key)
+    {
+        return Reconciled::Orphan(record.pid, record.start_time);
+    }
+
+    // 配置文件中的 token 已替换为环境变量引用，原值从保险库读取
+    let mut secrets = crate::commands::custom_tunnel::read_config_secrets(config_path);
+    secrets.extend(token_vault::tunnel_secrets(app_handle, &record.tunnel_key));
+    let output =
+        frpc_output::spawn_log_readers(app_handle, app_dir, &record.tunnel_key, secrets, true);
+    let adopted = AdoptedProcess {
+        pid: record.pid,
+        start_time: record.start_time,
+        config_path: record.config_path.clone(),
+        output_stop: output.stop_flag(),
+    };
+    if let Ok(mut map) = app_handle.state::<FrpcProcesses>().adopted.lock() {
+        map.insert(record.tunnel_key.clone(), adopted.clone());
+    }
+
+    // 开始读取输出后再次确认进程存活，避免把刚退出的进程标记为在线
+    if !is_process_alive(record.pid, record.start_time) {
+        take_adopted(&app_handle.state::<FrpcProcesses>(), &record.tunnel_key);
+        output.finish();
+        return Reconciled::Gone;
+    }
+
+    tunnel_status::set_status(app_handle, &record.tunnel_key, TunnelStatus::Online);
+    let _ = app_handle.emit(
+        "frpc-log",
+        LogMessage {
+            tunnel_key: record.tunnel_key.clone(),
+            message: format!(
+                "[I] [ChmlFrpLauncher] 已接管仍在运行的 frpc 进程 (PID: {})",
+                record.pid
+            ),
+            timestamp: get_timestamp(),
+        },
+    );
+
+    spawn_adopted_watcher(
+        app_handle.clone(),
+        record.tunnel_key.clone(),
+        adopted,
+        output,
+    );
+    Reconciled::Adopted
+}
+
+// 启动时处理上次运行遗留的 frpc 进程：仍在运行的接管，无法接管的同时结束
+pub fn reconcile_orphans(app_handle: &tauri::AppHandle) {
+    let Ok(app_dir) = app_handle.path().app_data_dir() else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(app_dir.join(PID_DIR)) else {
+        return;
+    };
+
+    let mut orphans = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let record = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<TunnelPidRecord>(&content).ok());
+
+        let reconciled = match &record {
+            Some(record) => reconcile_record(app_handle, &app_dir, record),
+            None => Reconciled::Gone,
+        };
+        match reconciled {
+            Reconciled::Adopted => {}
+            Reconciled::Orphan(pid, start_time) => {
+                orphans.push((pid, start_time));
+                let _ = std::fs::remove_file(&path);
+            }
+            Reconciled::Gone => {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    if !orphans.is_empty() {
+        terminate_pids(&orphans, ORPHAN_TERMINATE_TIMEOUT);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::commands::frpc_output::OutputTail;
+use crate::commands::tunnel_status;
+use crate::models::{
+    ChildState, FrpcChild, FrpcExitInfo, FrpcProcesses, StopMethod, TunnelKey, TunnelStatus,
+    TunnelStatuses,
+};
+use std::process::{Child, ExitStatus};
+use std::sync::atomic::Ordering;
+use std::thread;
+use sysinfo::{Pid, ProcessesToUpdate, System};
+use tauri::{Emitter, Manager};
+use tokio::sync::watch;
+
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        status.signal()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = status;
+        None
+    }
+}
+
+fn describe_exit(info: &FrpcExitInfo) -> String {
+    match (info.code, info.signal) {
+        (Some(code), _) => format!("退出码: {}", code),
+        (None, Some(signal)) => format!("信号: {}", signal),
+        (None, None) => "未知原因".to_string(),
+    }
+}
+
+// 记录进程退出信息，更新隧道状态并通知守护进程
+pub fn record_exit(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    pid: u32,
+    status: Option<ExitStatus>,
+    stop_method: Option<StopMethod>,
+) {
+    let info = FrpcExitInfo {
+        tunnel_key: tunnel_key.clone(),
+        pid,
+        code: status.as_ref().and_then(|s| s.code()),
+        signal: status.as_ref().and_then(exit_signal),
+        success: status.as_ref().is_some_and(|s| s.success()),
+        stop_method,
+        timestamp: chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string(),
+    };
+
+    crate::commands::process_adopt::remove_pid_file(app_handle, tunnel_key, pid);
+
+    let statuses = app_handle.state::<TunnelStatuses>();
+    if let Ok(mut exits) = statuses.exits.lock() {
+        exits.insert(tunnel_key.clone(), info.clone());
+    }
+
+    let current = tunnel_status::get_status(&statuses, tunnel_key);
+    let unexpected =
+        !info.success && !matches!(current, TunnelStatus::Stopped | TunnelStatus::Failed { .. });
+    if unexpected {
+        tunnel_status::set_status(
+            app_handle,
+            tunnel_key,
+            TunnelStatus::Failed {
+                reason: format!("frpc 进程异常退出 ({})", describe_exit(&info)),
+            },
+        );
+    } else {
+        tunnel_status::mark_process_exited(app_handle, tunnel_key);
+    }
+
+    let _ = app_handle.emit("frpc-exited", info);
+
+    crate::commands::process_guard::handle_tunnel_exit(app_handle, tunnel_key);
+}
+
+// 监听线程持有 Child 并阻塞等待进程退出，退出后回收进程并记录退出信息
+//
+// 调用方需在持有进程锁时调用并记录返回的 FrpcChild，保证监听线程回收时能找到对应记录
+pub fn spawn_exit_waiter(
+    app_handle: tauri::AppHandle,
+    tunnel_key: TunnelKey,
+    mut child: Child,
+    output: OutputTail,
+) -> Result<FrpcChild, String> {
+    let pid = child.id();
+    let (state_tx, state) = watch::channel(ChildState::Running);
+    let output_stop = output.stop_flag();
+
+    let result = thread::Builder::new()
+        .name(format!("frpc-waiter-{}", tunnel_key))
+        .spawn(move || {
+            let status = child.wait().ok();
+            // 日志读取线程结束后再记录退出，保证最后的日志已经输出
+            output.finish();
+            state_tx.send_replace(ChildState::Exited(status));
+
+            // 进程已被停止流程从列表中取出时，由停止流程记录退出信息
+            let owned = app_handle
+                .state::<FrpcProcesses>()
+                .processes
+                .lock()
+                .map(|mut procs| {
+                    let owned = procs.get(&tunnel_key).is_some_and(|c| c.pid == pid);
+                    if owned {
+                        procs.remove(&tunnel_key);
+                    }
+                    owned
+                })
+                .unwrap_or(false);
+            if owned {
+                record_exit(&app_handle, &tunnel_key, pid, status, None);
+            }
+        });
+
+    match result {
+        Ok(_) => Ok(FrpcChild { pid, state }),
+        Err(e) => {
+            kill_pid(pid);
+            output_stop.store(true, Ordering::SeqCst);
+            Err(format!("创建进程退出监听线程失败: {}", e))
+        }
+    }
+}
+
+// 通过 PID 结束进程，进程被监听线程回收前 PID 不会被复用
+pub fn kill_pid(pid: u32) -> bool {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    system.process(pid).is_some_and(|process| process.kill())
+}
+
+/// 等待监听线程回收进程，返回退出状态
+pub async fn wait_for_exit(state: &mut watch::Receiver<ChildState>) -> Option<ExitStatus> {
+    match state.wait_for(|s| matches!(s, ChildState::Exited(_))).await {
+        Ok(state) => match *state {
+            ChildState::Exited(status) => status,
+            ChildState::Running => None,
+        },
+        // 监听线程已结束，进程不会再被回收
+        Err(_) => None,
+    }
+}
@@ -1,27 +1,240 @@
-use crate::models::{FrpcProcesses, LogMessage, ProcessGuardInfo, ProcessGuardState, TunnelConfig, TunnelType};
+use crate::commands::{guard_rules, settings, token_vault};
+use crate::models::{
+    FrpcProcesses, GuardGaveUpMessage, GuardRestartInfo, GuardRuleAction, GuardRuleMatchedMessage,
+    GuardRules, LogMessage, PersistedGuardState, ProcessGuardConfig, ProcessGuardInfo,
+    ProcessGuardState, TunnelConfig, TunnelKey, TunnelType,
+};
 use std::sync::atomic::Ordering;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager, State};
 
-const STOP_GUARD_PATTERNS: &[&str] = &[
-    "token in login doesn't match token from configuration",
-    "authorization failed",
-    "invalid token",
-    "read: connection reset by peer",
-    "错误的用户token，此用户不存在",
-    "允许的隧道数量超出上限，请删除隧道或续费vip",
-    "不属于你",
-    "缺少用户token或隧道id参数",
-    "您目前为免费会员",
-    "客户端代理参数错误，配置文件与记录不匹配。请不要随意修改配置文件！",
-    "ChmlFrp API Error"
-];
-
 fn get_timestamp() -> String {
     chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string()
 }
 
+// 将守护开关、重启策略和受守护的隧道写入应用数据目录
+fn persist_guard_state(guard_state: &ProcessGuardState) {
+    let Some(path) = guard_state.state_file.lock().ok().and_then(|p| p.clone()) else {
+        return;
+    };
+
+    let persisted = PersistedGuardState {
+        enabled: guard_state.enabled.load(Ordering::SeqCst),
+        config: guard_state
+            .config
+            .lock()
+            .map(|c| c.clone())
+            .unwrap_or_default(),
+        guarded: guard_state
+            .guarded_processes
+            .lock()
+            .map(|g| g.values().map(strip_tokens).collect())
+            .unwrap_or_default(),
+    };
+
+    if let Err(e) = settings::save_at(&path, &persisted) {
+        eprintln!("[守护进程] 保存守护状态失败: {}", e);
+    }
+}
+
+// token 已在启动隧道时存入保险库，守护状态文件中不保留
+fn strip_tokens(info: &ProcessGuardInfo) -> ProcessGuardInfo {
+    let tunnel_type = match &info.tunnel_type {
+        TunnelType::Api { config } => TunnelType::Api {
+            config: config.without_tokens(),
+        },
+        other => other.clone(),
+    };
+    ProcessGuardInfo { tunnel_type }
+}
+
+// 启动时从磁盘恢复守护状态，需在 resume_guarded_tunnels 之前调用
+pub fn restore_guard_state(app_handle: &tauri::AppHandle) {
+    let Ok(path) = settings::get_settings_path::<PersistedGuardState>(app_handle) else {
+        return;
+    };
+
+    let guard_state = app_handle.state::<ProcessGuardState>();
+    if let Ok(mut state_file) = guard_state.state_file.lock() {
+        *state_file = Some(path);
+    }
+
+    let persisted: PersistedGuardState = match settings::load(app_handle) {
+        Ok(persisted) => persisted,
+        Err(e) => {
+            eprintln!("[守护进程] 读取守护状态失败: {}", e);
+            return;
+        }
+    };
+
+    guard_state.enabled.store(persisted.enabled, Ordering::SeqCst);
+    if let Ok(mut config) = guard_state.config.lock() {
+        *config = persisted.config;
+    }
+
+    if persisted.enabled {
+        if let Ok(mut guarded) = guard_state.guarded_processes.lock() {
+            for mut info in persisted.guarded {
+                // 状态文件中不含 token，从保险库补全，保险库中没有时无法重启该隧道
+                if let TunnelType::Api { config } = &mut info.tunnel_type {
+                    token_vault::fill_api_tokens(app_handle, config);
+                    if !config.has_tokens() {
+                        eprintln!(
+                            "[守护进程] 隧道 {} 的 token 未保存，跳过恢复守护",
+                            config.tunnel_id
+                        );
+                        continue;
+                    }
+                }
+                guarded.insert(info.key(), info);
+            }
+        }
+    }
+}
+
+enum RestartDecision {
+    Restart(Duration),
+    GiveUp(u32),
+    Skip,
+}
+
+// [0, 1) 之间的随机数，用于重启延迟抖动
+fn random_unit() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn compute_backoff(config: &ProcessGuardConfig, attempt: u32) -> Duration {
+    let delay = (config.base_delay_secs as f64 * 2f64.powi(attempt.min(16) as i32))
+        .min(config.max_delay_secs as f64);
+    let jitter = config.jitter_ratio.clamp(0.0, 1.0) * (random_unit() * 2.0 - 1.0);
+    Duration::from_secs_f64((delay * (1.0 + jitter)).max(0.0))
+}
+
+fn restarts_in_window(times: &std::collections::VecDeque<Instant>, window: Duration) -> u32 {
+    let now = Instant::now();
+    times
+        .iter()
+        .filter(|t| now.duration_since(**t) <= window)
+        .count() as u32
+}
+
+fn plan_restart(guard_state: &ProcessGuardState, tunnel_key: &TunnelKey) -> RestartDecision {
+    let config = guard_state
+        .config
+        .lock()
+        .map(|c| c.clone())
+        .unwrap_or_default();
+    let Ok(mut all_stats) = guard_state.restart_stats.lock() else {
+        return RestartDecision::Skip;
+    };
+    let stats = all_stats.entry(tunnel_key.clone()).or_default();
+
+    if stats.pending || stats.gave_up {
+        return RestartDecision::Skip;
+    }
+
+    let window = Duration::from_secs(config.restart_window_secs);
+    let now = Instant::now();
+    while stats
+        .restart_times
+        .front()
+        .is_some_and(|t| now.duration_since(*t) > window)
+    {
+        stats.restart_times.pop_front();
+    }
+
+    let attempts = stats.restart_times.len() as u32;
+    if attempts >= config.max_restarts {
+        stats.gave_up = true;
+        return RestartDecision::GiveUp(attempts);
+    }
+
+    stats.restart_times.push_back(now);
+    stats.total_restarts += 1;
+    stats.pending = true;
+    stats.last_restart_at = Some(get_timestamp());
+
+    RestartDecision::Restart(compute_backoff(&config, attempts))
+}
+
+fn finish_restart(guard_state: &ProcessGuardState, tunnel_key: &TunnelKey) {
+    if let Ok(mut all_stats) = guard_state.restart_stats.lock() {
+        if let Some(stats) = all_stats.get_mut(tunnel_key) {
+            stats.pending = false;
+        }
+    }
+}
+
+/// 守护进程是否已安排重启该隧道
+pub fn is_restart_pending(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey) -> bool {
+    app_handle
+        .state::<ProcessGuardState>()
+        .restart_stats
+        .lock()
+        .map(|all_stats| all_stats.get(tunnel_key).is_some_and(|s| s.pending))
+        .unwrap_or(false)
+}
+
+// 用户重新启动已放弃守护的隧道时，重置重启计数
+fn reset_gave_up_stats(guard_state: &ProcessGuardState, tunnel_key: &TunnelKey) {
+    if let Ok(mut all_stats) = guard_state.restart_stats.lock() {
+        if all_stats.get(tunnel_key).is_some_and(|s| s.gave_up) {
+            all_stats.remove(tunnel_key);
+        }
+    }
+}
+
+fn give_up(
+    app_handle: &tauri::AppHandle,
+    guard_state: &ProcessGuardState,
+    tunnel_key: &TunnelKey,
+    restarts: u32,
+) {
+    let window_secs = guard_state
+        .config
+        .lock()
+        .map(|c| c.restart_window_secs)
+        .unwrap_or_default();
+
+    if let Ok(mut guarded) = guard_state.guarded_processes.lock() {
+        guarded.remove(tunnel_key);
+    }
+    persist_guard_state(guard_state);
+
+    let _ = app_handle.emit(
+        "frpc-log",
+        LogMessage {
+            tunnel_key: tunnel_key.clone(),
+            message: format!(
+                "[W] [ChmlFrpLauncher] 隧道在 {} 秒内已重启 {} 次，守护进程停止自动重启",
+                window_secs, restarts
+            ),
+            timestamp: get_timestamp(),
+        },
+    );
+
+    let _ = app_handle.emit(
+        "guard-gave-up",
+        GuardGaveUpMessage {
+            tunnel_key: tunnel_key.clone(),
+            restarts,
+            window_secs,
+            timestamp: get_timestamp(),
+        },
+    );
+}
+
 #[tauri::command]
 pub async fn set_process_guard_enabled(
     enabled: bool,
@@ -36,8 +249,13 @@ pub async fn set_process_guard_enabled(
         if let Ok(mut stopped) = guard_state.manually_stopped.lock() {
             stopped.clear();
         }
+        if let Ok(mut all_stats) = guard_state.restart_stats.lock() {
+            all_stats.clear();
+        }
     }
 
+    persist_guard_state(&guard_state);
+
     Ok(format!(
         "守护进程已{}",
         if enabled { "启用" } else { "禁用" }
@@ -52,122 +270,235 @@ pub async fn get_process_guard_enabled(
 }
 
 #[tauri::command]
-pub async fn add_guarded_process(
-    tunnel_id: i32,
-    config: TunnelConfig,
+pub async fn get_process_guard_config(
+    guard_state: State<'_, ProcessGuardState>,
+) -> Result<ProcessGuardConfig, String> {
+    guard_state
+        .config
+        .lock()
+        .map(|c| c.clone())
+        .map_err(|e| format!("获取守护进程配置锁失败: {}", e))
+}
+
+#[tauri::command]
+pub async fn set_process_guard_config(
+    config: ProcessGuardConfig,
     guard_state: State<'_, ProcessGuardState>,
 ) -> Result<(), String> {
-    if !guard_state.enabled.load(Ordering::SeqCst) {
-        return Ok(());
+    if config.max_delay_secs < config.base_delay_secs {
+        return Err("最大重启间隔不能小于初始重启间隔".to_string());
+    }
+    if !(0.0..=1.0).contains(&config.jitter_ratio) {
+        return Err("抖动比例必须在 0 到 1 之间".to_string());
+    }
+    if config.max_restarts == 0 || config.restart_window_secs == 0 {
+        return Err("重启次数上限和统计窗口必须大于 0".to_string());
     }
 
-    let mut guarded = guard_state
-        .guarded_processes
+    {
+        let mut current = guard_state
+            .config
+            .lock()
+            .map_err(|e| format!("获取守护进程配置锁失败: {}", e))?;
+        *current = config;
+    }
+
+    persist_guard_state(&guard_state);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_guard_restart_stats(
+    guard_state: State<'_, ProcessGuardState>,
+) -> Result<Vec<GuardRestartInfo>, String> {
+    let window = guard_state
+        .config
+        .lock()
+        .map(|c| Duration::from_secs(c.restart_window_secs))
+        .map_err(|e| format!("获取守护进程配置锁失败: {}", e))?;
+
+    let all_stats = guard_state
+        .restart_stats
         .lock()
         .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
 
-    guarded.insert(
-        tunnel_id,
-        ProcessGuardInfo {
-            tunnel_id,
-            tunnel_type: TunnelType::Api { config },
-        },
-    );
+    Ok(all_stats
+        .iter()
+        .map(|(tunnel_key, stats)| GuardRestartInfo {
+            tunnel_key: tunnel_key.clone(),
+            total_restarts: stats.total_restarts,
+            restarts_in_window: restarts_in_window(&stats.restart_times, window),
+            pending: stats.pending,
+            gave_up: stats.gave_up,
+            last_restart_at: stats.last_restart_at.clone(),
+        })
+        .collect())
+}
+
+fn add_guarded(guard_state: &ProcessGuardState, info: ProcessGuardInfo) -> Result<(), String> {
+    if !guard_state.enabled.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let tunnel_key = info.key();
+    {
+        let mut guarded = guard_state
+            .guarded_processes
+            .lock()
+            .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
+
+        guarded.insert(tunnel_key.clone(), info);
+    }
 
     if let Ok(mut stopped) = guard_state.manually_stopped.lock() {
-        stopped.remove(&tunnel_id);
+        stopped.remove(&tunnel_key);
     }
 
+    reset_gave_up_stats(guard_state, &tunnel_key);
+    persist_guard_state(guard_state);
+
     Ok(())
 }
 
+#[tauri::command]
+pub async fn add_guarded_process(
+    config: TunnelConfig,
+    guard_state: State<'_, ProcessGuardState>,
+) -> Result<(), String> {
+    add_guarded(
+        &guard_state,
+        ProcessGuardInfo {
+            tunnel_type: TunnelType::Api { config },
+        },
+    )
+}
+
 #[tauri::command]
 pub async fn add_guarded_custom_tunnel(
-    tunnel_id_hash: i32,
     original_id: String,
     guard_state: State<'_, ProcessGuardState>,
 ) -> Result<(), String> {
-    if !guard_state.enabled.load(Ordering::SeqCst) {
-        return Ok(());
-    }
-
-    let mut guarded = guard_state
-        .guarded_processes
-        .lock()
-        .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
-
-    guarded.insert(
-        tunnel_id_hash,
+    add_guarded(
+        &guard_state,
         ProcessGuardInfo {
-            tunnel_id: tunnel_id_hash,
             tunnel_type: TunnelType::Custom { original_id },
         },
-    );
-
-    if let Ok(mut stopped) = guard_state.manually_stopped.lock() {
-        stopped.remove(&tunnel_id_hash);
-    }
-
-    Ok(())
+    )
 }
 
 #[tauri::command]
 pub async fn remove_guarded_process(
-    tunnel_id: i32,
+    tunnel_key: TunnelKey,
     guard_state: State<'_, ProcessGuardState>,
     is_manual_stop: bool,
 ) -> Result<(), String> {
-    let mut guarded = guard_state
-        .guarded_processes
-        .lock()
-        .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
+    {
+        let mut guarded = guard_state
+            .guarded_processes
+            .lock()
+            .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
 
-    guarded.remove(&tunnel_id);
+        guarded.remove(&tunnel_key);
+    }
 
     if is_manual_stop {
         if let Ok(mut stopped) = guard_state.manually_stopped.lock() {
-            stopped.insert(tunnel_id);
+            stopped.insert(tunnel_key.clone());
+        }
+        if let Ok(mut all_stats) = guard_state.restart_stats.lock() {
+            all_stats.remove(&tunnel_key);
         }
     }
 
-    Ok(())
-}
+    persist_guard_state(&guard_state);
 
-pub fn should_stop_guard_by_log(message: &str) -> Option<&'static str> {
-    let message_lower = message.to_lowercase();
-    STOP_GUARD_PATTERNS
-        .iter()
-        .find(|p| message_lower.contains(&p.to_lowercase()))
-        .copied()
+    Ok(())
 }
 
 #[tauri::command]
 pub async fn check_log_and_stop_guard(
     app_handle: tauri::AppHandle,
-    tunnel_id: i32,
+    tunnel_key: TunnelKey,
     log_message: String,
     guard_state: State<'_, ProcessGuardState>,
 ) -> Result<(), String> {
-    let Some(pattern) = should_stop_guard_by_log(&log_message) else {
+    let Some(rule) = guard_rules::match_rule(&app_handle.state::<GuardRules>(), &log_message)
+    else {
         return Ok(());
     };
 
-    eprintln!("[守护进程] 检测到隧道 {} 出现错误: {}", tunnel_id, pattern);
-    eprintln!("[守护进程] 停止对隧道 {} 的守护", tunnel_id);
+    eprintln!("[守护进程] 检测到隧道 {} 出现错误: {}", tunnel_key, rule.pattern);
 
-    {
-        let mut guarded = guard_state
-            .guarded_processes
-            .lock()
-            .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
-        guarded.remove(&tunnel_id);
-    }
+    let _ = app_handle.emit(
+        "guard-rule-matched",
+        GuardRuleMatchedMessage {
+            tunnel_key: tunnel_key.clone(),
+            rule_id: rule.id.clone(),
+            pattern: rule.pattern.clone(),
+            action: rule.action,
+            message: log_message.clone(),
+            timestamp: get_timestamp(),
+        },
+    );
+
+    let notice = match rule.action {
+        GuardRuleAction::NotifyOnly => {
+            format!("[W] [ChmlFrpLauncher] 检测到错误 \"{}\"", rule.pattern)
+        }
+        GuardRuleAction::StopGuard => {
+            eprintln!("[守护进程] 停止对隧道 {} 的守护", tunnel_key);
+            {
+                let mut guarded = guard_state
+                    .guarded_processes
+                    .lock()
+                    .map_err(|e| format!("获取守护进程锁失败: {}", e))?;
+                guarded.remove(&tunnel_key);
+            }
+            persist_guard_state(&guard_state);
+            format!("[W] [ChmlFrpLauncher] 检测到错误 \"{}\"，已停止守护进程", rule.pattern)
+        }
+        GuardRuleAction::StopTunnel => {
+            eprintln!("[守护进程] 停止隧道 {}", tunnel_key);
+            // 停止隧道需要等待进程退出，交给单独的任务处理，避免阻塞日志读取线程
+            let app_handle = app_handle.clone();
+            let tunnel_key = tunnel_key.clone();
+            tauri::async_runtime::spawn(async move {
+                let guard_state = app_handle.state::<ProcessGuardState>();
+                let result = match &tunnel_key {
+                    TunnelKey::Api(tunnel_id) => {
+                        crate::commands::process::stop_frpc(
+                            app_handle.clone(),
+                            *tunnel_id,
+                            guard_state,
+                        )
+                        .await
+                    }
+                    TunnelKey::Custom(tunnel_id) => {
+                        crate::commands::custom_tunnel::stop_custom_tunnel(
+                            app_handle.clone(),
+                            tunnel_id.clone(),
+                            guard_state,
+                        )
+                        .await
+                    }
+                };
+                if let Err(e) = result {
+                    eprintln!("[守护进程] 停止隧道 {} 失败: {}", tunnel_key, e);
+                }
+            });
+            format!(
+                "[W] [ChmlFrpLauncher] 检测到错误 \"{}\"，正在停止隧道",
+                rule.pattern
+            )
+        }
+    };
 
     let _ = app_handle.emit(
         "frpc-log",
         LogMessage {
-            tunnel_id,
-            message: format!("[W] [ChmlFrpLauncher] 检测到错误 \"{}\"，已停止守护进程", pattern),
+            tunnel_key,
+            message: notice,
             timestamp: get_timestamp(),
         },
     );
@@ -175,40 +506,37 @@ pub async fn check_log_and_stop_guard(
     Ok(())
 }
 
-fn is_tunnel_running(processes: &State<'_, FrpcProcesses>, tunnel_id: i32) -> bool {
-    let Ok(mut procs) = processes.processes.lock() else {
-        return false;
-    };
-
-    let Some(child) = procs.get_mut(&tunnel_id) else {
-        return false;
-    };
-
-    match child.try_wait() {
-        Ok(None) => true,
-        Ok(Some(_)) | Err(_) => {
-            procs.remove(&tunnel_id);
-            false
-        }
-    }
-}
-
-fn is_manually_stopped(guard_state: &State<'_, ProcessGuardState>, tunnel_id: i32) -> bool {
+fn is_manually_stopped(guard_state: &State<'_, ProcessGuardState>, tunnel_key: &TunnelKey) -> bool {
     guard_state
         .manually_stopped
         .lock()
         .ok()
-        .map(|s| s.contains(&tunnel_id))
+        .map(|s| s.contains(tunnel_key))
         .unwrap_or(true)
 }
 
-fn restart_tunnel(app_handle: tauri::AppHandle, info: ProcessGuardInfo) {
+fn restart_tunnel(app_handle: tauri::AppHandle, info: ProcessGuardInfo, delay: Duration) {
     thread::spawn(move || {
-        thread::sleep(Duration::from_secs(1));
+        thread::sleep(delay);
 
         let processes_state = app_handle.state::<FrpcProcesses>();
         let guard_state_state = app_handle.state::<ProcessGuardState>();
-        let tunnel_id = info.tunnel_id;
+        let tunnel_key = info.key();
+
+        let still_guarded = guard_state_state
+            .guarded_processes
+            .lock()
+            .map(|g| g.contains_key(&tunnel_key))
+            .unwrap_or(false);
+        // 重启前清除等待标记，新进程若立即退出可再次触发守护
+        finish_restart(&guard_state_state, &tunnel_key);
+        if !still_guarded || is_manually_stopped(&guard_state_state, &tunnel_key) {
+            return;
+        }
+        // 等待期间隧道可能已被自动启动或用户手动启动
+        if crate::commands::process_adopt::is_running(&processes_state, &tunnel_key) {
+            return;
+        }
 
         let result = match info.tunnel_type {
             TunnelType::Api { config } => {
@@ -240,72 +568,210 @@ fn restart_tunnel(app_handle: tauri::AppHandle, info: ProcessGuardInfo) {
                 let _ = app_handle.emit(
                     "tunnel-auto-restarted",
                     serde_json::json!({
-                        "tunnel_id": tunnel_id,
+                        "tunnel_key": tunnel_key,
                         "timestamp": get_timestamp(),
                     }),
                 );
             }
+            // 与其他启动并发时隧道已在运行，保留守护
+            Err(_)
+                if crate::commands::process_adopt::is_running(
+                    &app_handle.state::<FrpcProcesses>(),
+                    &tunnel_key,
+                ) => {}
             Err(e) => {
                 let _ = app_handle.emit(
                     "frpc-log",
                     LogMessage {
-                        tunnel_id,
+                        tunnel_key: tunnel_key.clone(),
                         message: format!("[E] [ChmlFrpLauncher] 守护进程重启失败: {}", e),
                         timestamp: get_timestamp(),
                     },
                 );
 
                 if let Ok(mut guarded) = app_handle.state::<ProcessGuardState>().guarded_processes.lock() {
-                    guarded.remove(&tunnel_id);
+                    guarded.remove(&tunnel_key);
                 }
+                persist_guard_state(&app_handle.state::<ProcessGuardState>());
             }
         }
     });
 }
 
-pub fn start_guard_monitor(app_handle: tauri::AppHandle) {
-    thread::spawn(move || {
-        loop {
-            thread::sleep(Duration::from_secs(3));
+// 隧道进程退出后由退出监听线程调用，按守护策略安排重启
+pub fn handle_tunnel_exit(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey) {
+    let guard_state = app_handle.state::<ProcessGuardState>();
 
-            let guard_state = app_handle.state::<ProcessGuardState>();
-            let processes = app_handle.state::<FrpcProcesses>();
+    if !guard_state.enabled.load(Ordering::SeqCst)
+        || crate::commands::shutdown::is_shutting_down()
+    {
+        return;
+    }
 
-            if !guard_state.enabled.load(Ordering::SeqCst) {
-                continue;
-            }
+    let Some(info) = guard_state
+        .guarded_processes
+        .lock()
+        .ok()
+        .and_then(|guarded| guarded.get(tunnel_key).cloned())
+    else {
+        return;
+    };
 
-            let guarded_list: Vec<ProcessGuardInfo> = match guard_state.guarded_processes.lock() {
-                Ok(guarded) => guarded.values().cloned().collect(),
-                Err(_) => continue,
-            };
+    if is_manually_stopped(&guard_state, tunnel_key) {
+        return;
+    }
 
-            if guarded_list.is_empty() {
-                continue;
-            }
+    if crate::commands::process_adopt::is_running(&app_handle.state::<FrpcProcesses>(), tunnel_key) {
+        return;
+    }
 
-            for info in guarded_list {
-                let tunnel_id = info.tunnel_id;
+    let delay = match plan_restart(&guard_state, tunnel_key) {
+        RestartDecision::Restart(delay) => delay,
+        RestartDecision::GiveUp(restarts) => {
+            give_up(app_handle, &guard_state, tunnel_key, restarts);
+            return;
+        }
+        RestartDecision::Skip => return,
+    };
 
-                if is_manually_stopped(&guard_state, tunnel_id) {
-                    continue;
-                }
+    let _ = app_handle.emit(
+        "frpc-log",
+        LogMessage {
+            tunnel_key: tunnel_key.clone(),
+            message: format!(
+                "[W] [ChmlFrpLauncher] 检测到进程离线，触发守护进程，{:.1} 秒后自动重启",
+                delay.as_secs_f64()
+            ),
+            timestamp: get_timestamp(),
+        },
+    );
 
-                if is_tunnel_running(&processes, tunnel_id) {
-                    continue;
-                }
+    restart_tunnel(app_handle.clone(), info, delay);
+}
 
-                let _ = app_handle.emit(
-                    "frpc-log",
-                    LogMessage {
-                        tunnel_id,
-                        message: "[W] [ChmlFrpLauncher] 检测到进程离线，触发守护进程，自动重启中".to_string(),
-                        timestamp: get_timestamp(),
-                    },
-                );
+// 启动时恢复的守护隧道尚未运行，逐个交给守护策略处理
+pub fn resume_guarded_tunnels(app_handle: &tauri::AppHandle) {
+    let guard_state = app_handle.state::<ProcessGuardState>();
+    let tunnel_keys: Vec<TunnelKey> = match guard_state.guarded_processes.lock() {
+        Ok(guarded) => guarded.keys().cloned().collect(),
+        Err(_) => return,
+    };
 
-                restart_tunnel(app_handle.clone(), info);
-            }
+    for tunnel_key in tunnel_keys {
+        handle_tunnel_exit(app_handle, &tunnel_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard_state(config: ProcessGuardConfig) -> ProcessGuardState {
+        let state = ProcessGuardState::new();
+        *state.config.lock().unwrap() = config;
+        state
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max_delay() {
+        let config = ProcessGuardConfig {
+            base_delay_secs: 2,
+            max_delay_secs: 30,
+            jitter_ratio: 0.0,
+            ..Default::default()
+        };
+        let delays: Vec<u64> = (0..6)
+            .map(|attempt| compute_backoff(&config, attempt).as_secs())
+            .collect();
+        assert_eq!(delays, [2, 4, 8, 16, 30, 30]);
+        assert_eq!(compute_backoff(&config, u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn backoff_jitter_stays_within_ratio() {
+        let config = ProcessGuardConfig {
+            base_delay_secs: 10,
+            max_delay_secs: 10,
+            jitter_ratio: 0.2,
+            ..Default::default()
+        };
+        for _ in 0..100 {
+            let delay = compute_backoff(&config, 0).as_secs_f64();
+            assert!((8.0..=12.0).contains(&delay), "{}", delay);
         }
-    });
+
+        // 超出范围的抖动比例按 1 处理，延迟不会为负
+        let config = ProcessGuardConfig {
+            jitter_ratio: 5.0,
+            ..config
+        };
+        for _ in 0..100 {
+            assert!(compute_backoff(&config, 0) <= Duration::from_secs(20));
+        }
+    }
+
+    #[test]
+    fn restarts_stop_after_budget() {
+        let state = guard_state(ProcessGuardConfig {
+            max_restarts: 2,
+            ..Default::default()
+        });
+        let api = TunnelKey::Api(1);
+        let custom = TunnelKey::Custom("home".to_string());
+
+        for _ in 0..2 {
+            assert!(matches!(
+                plan_restart(&state, &api),
+                RestartDecision::Restart(_)
+            ));
+            // 上一次重启完成前不会再次安排
+            assert!(matches!(plan_restart(&state, &api), RestartDecision::Skip));
+            finish_restart(&state, &api);
+        }
+        assert!(matches!(
+            plan_restart(&state, &api),
+            RestartDecision::GiveUp(2)
+        ));
+        assert!(matches!(plan_restart(&state, &api), RestartDecision::Skip));
+
+        // 其他隧道不受影响，手动重启后重新计数
+        assert!(matches!(
+            plan_restart(&state, &custom),
+            RestartDecision::Restart(_)
+        ));
+        reset_gave_up_stats(&state, &api);
+        assert!(matches!(
+            plan_restart(&state, &api),
+            RestartDecision::Restart(_)
+        ));
+    }
+
+    #[test]
+    fn restarts_outside_window_are_forgotten() {
+        let state = guard_state(ProcessGuardConfig {
+            max_restarts: 1,
+            restart_window_secs: 60,
+            ..Default::default()
+        });
+        let key = TunnelKey::Api(1);
+        let Some(long_ago) = Instant::now().checked_sub(Duration::from_secs(120)) else {
+            return;
+        };
+        state
+            .restart_stats
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .restart_times
+            .push_back(long_ago);
+
+        assert!(matches!(
+            plan_restart(&state, &key),
+            RestartDecision::Restart(_)
+        ));
+        let stats = state.restart_stats.lock().unwrap();
+        assert_eq!(stats[&key].restart_times.len(), 1);
+        assert_eq!(stats[&key].total_restarts, 1);
+    }
 }
@@ -0,0 +1,178 @@
+use crate::commands::settings::{self, LauncherSettings};
+use crate::commands::{process_adopt, process_exit, tunnel_status};
+use crate::models::{
+    AdoptedProcess, FrpcChild, FrpcProcesses, LogMessage, StopMethod, TunnelKey, TunnelStatus,
+};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+const MAX_GRACE_SECS: u64 = 60;
+const KILL_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn get_timestamp() -> String {
+    chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string()
+}
+
+// 请求 frpc 自行退出，以便关闭与节点的控制连接
+#[cfg(unix)]
+fn request_terminate(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    unsafe { libc::kill(pid, libc::SIGTERM) == 0 }
+}
+
+// Windows 下无窗口的控制台进程无法接收关闭信号，只能直接结束
+#[cfg(not(unix))]
+fn request_terminate(_pid: u32) -> bool {
+    false
+}
+
+fn emit_stop_log(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    method: StopMethod,
+    grace: Duration,
+) {
+    let message = match method {
+        StopMethod::Graceful => "[I] [ChmlFrpLauncher] frpc 已正常退出".to_string(),
+        StopMethod::Forced if grace.is_zero() || cfg!(not(unix)) => {
+            "[W] [ChmlFrpLauncher] 已强制结束 frpc 进程".to_string()
+        }
+        StopMethod::Forced => format!(
+            "[W] [ChmlFrpLauncher] frpc 在 {} 秒内未退出，已强制结束",
+            grace.as_secs()
+        ),
+    };
+    let _ = app_handle.emit(
+        "frpc-log",
+        LogMessage {
+            tunnel_key: tunnel_key.clone(),
+            message,
+            timestamp: get_timestamp(),
+        },
+    );
+}
+
+fn get_grace_period(app_handle: &tauri::AppHandle) -> Duration {
+    let secs = settings::load::<LauncherSettings>(app_handle)
+        .map(|settings| settings.stop_grace_secs)
+        .unwrap_or_else(|_| LauncherSettings::default().stop_grace_secs);
+    Duration::from_secs(secs.min(MAX_GRACE_SECS))
+}
+
+// 先发送 SIGTERM 并在宽限期内等待退出（期间日志线程继续输出最后的日志），超时后强制结束
+async fn terminate_child(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    mut child: FrpcChild,
+) -> Result<StopMethod, String> {
+    let pid = child.pid;
+    let grace = get_grace_period(app_handle);
+
+    let graceful = if !grace.is_zero() && request_terminate(pid) {
+        tokio::time::timeout(grace, process_exit::wait_for_exit(&mut child.state))
+            .await
+            .ok()
+    } else {
+        None
+    };
+
+    let (method, status) = match graceful {
+        Some(status) => (StopMethod::Graceful, status),
+        None => {
+            process_exit::kill_pid(pid);
+            let waited =
+                tokio::time::timeout(KILL_TIMEOUT, process_exit::wait_for_exit(&mut child.state))
+                    .await;
+            match waited {
+                Ok(status) => (StopMethod::Forced, status),
+                Err(_) => {
+                    // 进程仍在运行，放回列表由监听线程继续负责
+                    if let Ok(mut procs) = app_handle.state::<FrpcProcesses>().processes.lock() {
+                        procs.entry(tunnel_key.clone()).or_insert(child);
+                    }
+                    return Err(format!("停止进程失败: 进程 {} 未退出", pid));
+                }
+            }
+        }
+    };
+
+    emit_stop_log(app_handle, tunnel_key, method, grace);
+
+    process_exit::record_exit(app_handle, tunnel_key, pid, status, Some(method));
+
+    Ok(method)
+}
+
+// 停止隧道对应的子进程或接管的进程，隧道未运行时返回 None
+pub async fn stop_tunnel_process(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+) -> Option<Result<StopMethod, String>> {
+    let processes = app_handle.state::<FrpcProcesses>();
+    let child = processes
+        .processes
+        .lock()
+        .ok()
+        .and_then(|mut procs| procs.remove(tunnel_key));
+
+    if let Some(child) = child {
+        tunnel_status::set_status(app_handle, tunnel_key, TunnelStatus::Stopped);
+        return Some(terminate_child(app_handle, tunnel_key, child).await);
+    }
+
+    let adopted = process_adopt::take_adopted(&processes, tunnel_key)?;
+    tunnel_status::set_status(app_handle, tunnel_key, TunnelStatus::Stopped);
+    Some(terminate_adopted(app_handle, tunnel_key, adopted).await)
+}
+
+// 停止接管的进程，流程与子进程一致，但只能通过 PID 操作
+async fn terminate_adopted(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    adopted: AdoptedProcess,
+) -> Result<StopMethod, String> {
+    let grace = get_grace_period(app_handle);
+    let (pid, start_time) = (adopted.pid, adopted.start_time);
+
+    let graceful = tauri::async_runtime::spawn_blocking(move || {
+        process_adopt::terminate_pid(pid, start_time, grace)
+    })
+    .await
+    .map_err(|e| format!("停止进程失败: {}", e))?;
+    adopted.output_stop.store(true, Ordering::SeqCst);
+
+    let method = if graceful {
+        StopMethod::Graceful
+    } else {
+        StopMethod::Forced
+    };
+    emit_stop_log(app_handle, tunnel_key, method, grace);
+    process_exit::record_exit(app_handle, tunnel_key, pid, None, Some(method));
+
+    Ok(method)
+}
+
+/// 获取停止隧道时等待 frpc 自行退出的时间（秒）
+///
+/// 仅在 Unix 上生效，Windows 下停止隧道时总是直接结束进程
+#[tauri::command]
+pub async fn get_stop_grace_period(app_handle: tauri::AppHandle) -> Result<u64, String> {
+    Ok(get_grace_period(&app_handle).as_secs())
+}
+
+/// 设置停止隧道时等待 frpc 自行退出的时间（秒），为 0 时直接结束进程
+///
+/// 仅在 Unix 上生效，Windows 下停止隧道时总是直接结束进程
+#[tauri::command]
+pub async fn set_stop_grace_period(app_handle: tauri::AppHandle, secs: u64) -> Result<(), String> {
+    if secs > MAX_GRACE_SECS {
+        return Err(format!("等待时间不能超过 {} 秒", MAX_GRACE_SECS));
+    }
+    settings::update(&app_handle, |settings: &mut LauncherSettings| {
+        settings.stop_grace_secs = secs;
+        Ok(())
+    })
+}
@@ -0,0 +1,571 @@
+use crate::commands::crypto;
+use crate::commands::custom_tunnel::{self, CustomTunnel};
+use crate::commands::frpc_config::ConfigFormat;
+use crate::commands::guard_rules;
+use crate::commands::settings::{self, LauncherSettings, SettingsSchema};
+use crate::models::{
+    AutoStartStore, GuardRule, ProfileConflictStrategy, ProfileImportAction, ProfileImportItem,
+    ProfileImportReport, ProfileItemCategory, ProfileManifest, ProfileManifestEntry, TunnelKind,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+
+const PROFILE_FORMAT_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const BACKGROUND_DIR: &str = "backgrounds";
+const CONFIG_FILE_PREFIX: &str = "z_";
+// 加密备份的文件头，后接盐值和加密后的 zip 数据
+const ENCRYPTED_MAGIC: &[u8] = b"CHMLPRF1";
+const MAX_ARCHIVE_ENTRIES: usize = 1024;
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+fn get_app_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用目录失败: {}", e))
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+// 备份中只允许出现这些路径，防止导入时写到应用目录之外
+fn is_allowed_path(path: &str) -> bool {
+    let is_plain_name = |name: &str| {
+        !name.is_empty()
+            && name != "."
+            && name != ".."
+            && !name.contains('/')
+            && !name.contains('\\')
+            && !name.contains(':')
+    };
+
+    if path == LauncherSettings::FILE_NAME
+        || path == AutoStartStore::FILE_NAME
+        || path == <Vec<CustomTunnel>>::FILE_NAME
+        || path == <Vec<GuardRule>>::FILE_NAME
+    {
+        return true;
+    }
+    if let Some(name) = path.strip_prefix(&format!("{}/", BACKGROUND_DIR)) {
+        return is_plain_name(name);
+    }
+    path.starts_with(CONFIG_FILE_PREFIX) && is_plain_name(path)
+}
+
+fn collect_profile_files(app_handle: &tauri::AppHandle) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let app_dir = get_app_dir(app_handle)?;
+    let mut files = Vec::new();
+
+    let mut add_file = |relative: String, path: &Path| -> Result<(), String> {
+        if path.is_file() {
+            let content = fs::read(path).map_err(|e| format!("读取 {} 失败: {}", relative, e))?;
+            files.push((relative, content));
+        }
+        Ok(())
+    };
+
+    for name in [
+        LauncherSettings::FILE_NAME,
+        AutoStartStore::FILE_NAME,
+        <Vec<CustomTunnel>>::FILE_NAME,
+        <Vec<GuardRule>>::FILE_NAME,
+    ] {
+        add_file(name.to_string(), &app_dir.join(name))?;
+    }
+
+    // 只导出列表中登记过的自定义隧道配置，保险库与本机绑定，token 还原后写入备份
+    let tunnels: Vec<CustomTunnel> = settings::load(app_handle)?;
+    let mut config_files = Vec::new();
+    for tunnel in &tunnels {
+        let path = app_dir.join(&tunnel.config_file);
+        if is_allowed_path(&tunnel.config_file) && path.is_file() {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("读取 {} 失败: {}", tunnel.config_file, e))?;
+            let content =
+                custom_tunnel::materialize_config_content(app_handle, &tunnel.id, &content)?;
+            config_files.push((tunnel.config_file.clone(), content.into_bytes()));
+        }
+    }
+
+    let background_dir = app_dir.join(BACKGROUND_DIR);
+    if let Ok(entries) = fs::read_dir(&background_dir) {
+        for entry in entries.flatten() {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let relative = format!("{}/{}", BACKGROUND_DIR, name);
+            if is_allowed_path(&relative) {
+                add_file(relative, &entry.path())?;
+            }
+        }
+    }
+
+    files.extend(config_files);
+    Ok(files)
+}
+
+fn build_archive(
+    manifest: &ProfileManifest,
+    files: &[(String, Vec<u8>)],
+) -> Result<Vec<u8>, String> {
+    let map_err = |e: zip::result::ZipError| format!("生成备份文件失败: {}", e);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+
+    let manifest_content =
+        serde_json::to_vec_pretty(manifest).map_err(|e| format!("序列化备份清单失败: {}", e))?;
+    writer.start_file(MANIFEST_FILE, options).map_err(map_err)?;
+    writer
+        .write_all(&manifest_content)
+        .map_err(|e| format!("生成备份文件失败: {}", e))?;
+
+    for (name, content) in files {
+        writer.start_file(name.as_str(), options).map_err(map_err)?;
+        writer
+            .write_all(content)
+            .map_err(|e| format!("生成备份文件失败: {}", e))?;
+    }
+
+    Ok(writer.finish().map_err(map_err)?.into_inner())
+}
+
+fn read_archive(data: &[u8]) -> Result<HashMap<String, Vec<u8>>, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(data)).map_err(|e| format!("读取备份文件失败: {}", e))?;
+    if archive.len() > MAX_ARCHIVE_ENTRIES {
+        return Err("备份文件包含的条目过多".to_string());
+    }
+
+    let mut files = HashMap::new();
+    for index in 0..archive.len() {
+        let file = archive
+            .by_index(index)
+            .map_err(|e| format!("读取备份文件失败: {}", e))?;
+        if file.is_dir() {
+            continue;
+        }
+        if file.size() > MAX_ENTRY_SIZE {
+            return Err(format!("备份中的 {} 过大", file.name()));
+        }
+
+        let name = file.name().to_string();
+        let mut content = Vec::new();
+        // 不信任条目头中记录的大小，读取时同样限制长度
+        file.take(MAX_ENTRY_SIZE + 1)
+            .read_to_end(&mut content)
+            .map_err(|e| format!("读取备份中的 {} 失败: {}", name, e))?;
+        if content.len() as u64 > MAX_ENTRY_SIZE {
+            return Err(format!("备份中的 {} 过大", name));
+        }
+        files.insert(name, content);
+    }
+    Ok(files)
+}
+
+fn encrypt_archive(archive: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let salt = crypto::random_bytes::<{ crypto::SALT_LEN }>();
+    let key = crypto::derive_key(passphrase.as_bytes(), &salt)?;
+    let sealed = crypto::seal(&key, archive)?;
+
+    let mut output = Vec::with_capacity(ENCRYPTED_MAGIC.len() + salt.len() + sealed.len());
+    output.extend_from_slice(ENCRYPTED_MAGIC);
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&sealed);
+    Ok(output)
+}
+
+fn decrypt_archive(data: &[u8], passphrase: Option<&str>) -> Result<Vec<u8>, String> {
+    let Some(payload) = data.strip_prefix(ENCRYPTED_MAGIC) else {
+        return Ok(data.to_vec());
+    };
+    let passphrase = passphrase
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "该备份已加密，请输入密码".to_string())?;
+    if payload.len() < crypto::SALT_LEN {
+        return Err("备份文件已损坏".to_string());
+    }
+
+    let (salt, sealed) = payload.split_at(crypto::SALT_LEN);
+    let key = crypto::derive_key(passphrase.as_bytes(), salt)?;
+    crypto::open(&key, sealed).map_err(|_| "密码错误或备份文件已损坏".to_string())
+}
+
+// 按清单校验每个文件，只返回清单中登记且校验通过的文件
+fn verify_archive(
+    mut files: HashMap<String, Vec<u8>>,
+) -> Result<(ProfileManifest, HashMap<String, Vec<u8>>), String> {
+    let manifest_content = files
+        .remove(MANIFEST_FILE)
+        .ok_or_else(|| "备份文件缺少清单，可能不是有效的配置备份".to_string())?;
+    let manifest: ProfileManifest = serde_json::from_slice(&manifest_content)
+        .map_err(|e| format!("解析备份清单失败: {}", e))?;
+
+    if manifest.format_version > PROFILE_FORMAT_VERSION {
+        return Err(format!(
+            "备份格式版本 {} 高于当前支持的版本 {}，请升级启动器",
+            manifest.format_version, PROFILE_FORMAT_VERSION
+        ));
+    }
+
+    let mut verified = HashMap::new();
+    for entry in &manifest.files {
+        if !is_allowed_path(&entry.path) {
+            return Err(format!("备份中包含不允许的路径: {}", entry.path));
+        }
+        let content = files
+            .remove(&entry.path)
+            .ok_or_else(|| format!("备份文件缺少 {}", entry.path))?;
+        if sha256_hex(&content) != entry.sha256 {
+            return Err(format!("备份中的 {} 校验失败", entry.path));
+        }
+        verified.insert(entry.path.clone(), content);
+    }
+
+    Ok((manifest, verified))
+}
+
+fn decide(exists: bool, strategy: ProfileConflictStrategy) -> ProfileImportAction {
+    match (exists, strategy) {
+        (false, _) => ProfileImportAction::Create,
+        (true, ProfileConflictStrategy::Overwrite) => ProfileImportAction::Overwrite,
+        (true, ProfileConflictStrategy::Skip) => ProfileImportAction::Skip,
+    }
+}
+
+fn parse_settings<T: SettingsSchema>(
+    files: &HashMap<String, Vec<u8>>,
+) -> Result<Option<T>, String> {
+    files
+        .get(T::FILE_NAME)
+        .map(|content| settings::parse::<T>(&String::from_utf8_lossy(content)))
+        .transpose()
+}
+
+fn apply_profile(
+    app_handle: &tauri::AppHandle,
+    files: &HashMap<String, Vec<u8>>,
+    strategy: ProfileConflictStrategy,
+    dry_run: bool,
+) -> Result<Vec<ProfileImportItem>, String> {
+    let app_dir = get_app_dir(app_handle)?;
+    let mut items = Vec::new();
+    let mut push = |category, name: &str, action| {
+        items.push(ProfileImportItem {
+            category,
+            name: name.to_string(),
+            action,
+        });
+        action != ProfileImportAction::Skip && !dry_run
+    };
+
+    // 先解析全部内容，避免导入到一半才发现文件有误
+    let launcher = parse_settings::<LauncherSettings>(files)?;
+    let tunnels = parse_settings::<Vec<CustomTunnel>>(files)?;
+    let auto_start = parse_settings::<AutoStartStore>(files)?;
+    let rules = parse_settings::<Vec<GuardRule>>(files)?;
+
+    if let Some(launcher) = launcher {
+        let exists = app_dir.join(LauncherSettings::FILE_NAME).exists();
+        let action = decide(exists, strategy);
+        if push(
+            ProfileItemCategory::Settings,
+            LauncherSettings::FILE_NAME,
+            action,
+        ) {
+            settings::update(app_handle, |current: &mut LauncherSettings| {
+                *current = launcher;
+                Ok(())
+            })?;
+        }
+    }
+
+    if let Some(tunnels) = tunnels {
+        let existing: Vec<CustomTunnel> = settings::load(app_handle)?;
+        let mut imported = Vec::new();
+
+        for tunnel in tunnels {
+            // 隧道 id 会用于拼接配置文件名和删除旧配置，必须与配置文件名对应
+            let matches_config = ConfigFormat::ALL.iter().any(|format| {
+                custom_tunnel::get_config_file_name(&tunnel.id, *format) == tunnel.config_file
+            });
+            if !custom_tunnel::is_valid_tunnel_id(&tunnel.id) || !matches_config {
+                return Err(format!("备份文件中的隧道 {} 无效", tunnel.id));
+            }
+
+            let content = files
+                .get(&tunnel.config_file)
+                .ok_or_else(|| format!("备份文件缺少隧道 {} 的配置", tunnel.id))?;
+
+            let exists = existing.iter().any(|t| t.id == tunnel.id);
+            let action = decide(exists, strategy);
+            if push(ProfileItemCategory::CustomTunnel, &tunnel.id, action) {
+                custom_tunnel::remove_config_files(&app_dir, &tunnel.id, None)?;
+                custom_tunnel::write_config_file(
+                    app_handle,
+                    &app_dir,
+                    &tunnel.id,
+                    &tunnel.config_file,
+                    &String::from_utf8_lossy(content),
+                )?;
+                imported.push(tunnel);
+            }
+        }
+
+        if !imported.is_empty() {
+            settings::update(app_handle, |current: &mut Vec<CustomTunnel>| {
+                for tunnel in imported {
+                    match current.iter_mut().find(|t| t.id == tunnel.id) {
+                        Some(existing) => *existing = tunnel,
+                        None => current.push(tunnel),
+                    }
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    if let Some(auto_start) = auto_start {
+        let existing: AutoStartStore = settings::load(app_handle)?;
+        let mut imported = Vec::new();
+
+        let mut entries = auto_start.entries;
+        entries.sort_by_key(|entry| entry.order);
+        for entry in entries {
+            let exists = existing
+                .entries
+                .iter()
+                .any(|e| e.kind == entry.kind && e.tunnel_id == entry.tunnel_id);
+            let kind = match entry.kind {
+                TunnelKind::Api => "api",
+                TunnelKind::Custom => "custom",
+            };
+            let name = format!("{}_{}", kind, entry.tunnel_id);
+            if push(
+                ProfileItemCategory::AutoStart,
+                &name,
+                decide(exists, strategy),
+            ) {
+                imported.push(entry);
+            }
+        }
+
+        if !imported.is_empty() {
+            settings::update(app_handle, |current: &mut AutoStartStore| {
+                for mut entry in imported {
+                    match current
+                        .entries
+                        .iter_mut()
+                        .find(|e| e.kind == entry.kind && e.tunnel_id == entry.tunnel_id)
+                    {
+                        Some(existing) => existing.delay_ms = entry.delay_ms,
+                        None => {
+                            // 新导入的隧道排在已有隧道之后
+                            entry.order = current
+                                .entries
+                                .iter()
+                                .map(|e| e.order + 1)
+                                .max()
+                                .unwrap_or(0);
+                            current.entries.push(entry);
+                        }
+                    }
+                }
+                Ok(())
+            })?;
+        }
+    }
+
+    if let Some(rules) = rules {
+        let path = app_dir.join(<Vec<GuardRule>>::FILE_NAME);
+        if push(
+            ProfileItemCategory::GuardRules,
+            <Vec<GuardRule>>::FILE_NAME,
+            decide(path.exists(), strategy),
+        ) {
+            settings::save(app_handle, &rules)?;
+            guard_rules::load_guard_rules(app_handle);
+        }
+    }
+
+    let mut backgrounds: Vec<(&String, &Vec<u8>)> = files
+        .iter()
+        .filter(|(path, _)| path.starts_with(&format!("{}/", BACKGROUND_DIR)))
+        .collect();
+    backgrounds.sort_by(|a, b| a.0.cmp(b.0));
+    for (relative, content) in backgrounds {
+        let path = app_dir.join(relative);
+        if push(
+            ProfileItemCategory::Background,
+            relative,
+            decide(path.exists(), strategy),
+        ) {
+            settings::write_atomic(&path, content, false)?;
+        }
+    }
+
+    Ok(items)
+}
+
+/// 将自定义隧道、自动启动设置、守护规则和背景导出为单个备份文件
+#[tauri::command]
+pub async fn export_profile(
+    app_handle: tauri::AppHandle,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<ProfileManifest, String> {
+    let files = collect_profile_files(&app_handle)?;
+
+    let manifest = ProfileManifest {
+        format_version: PROFILE_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        files: files
+            .iter()
+            .map(|(name, content)| ProfileManifestEntry {
+                path: name.clone(),
+                size: content.len() as u64,
+                sha256: sha256_hex(content),
+            })
+            .collect(),
+    };
+
+    let archive = build_archive(&manifest, &files)?;
+    let output = match passphrase.as_deref().filter(|p| !p.is_empty()) {
+        Some(passphrase) => encrypt_archive(&archive, passphrase)?,
+        None => archive,
+    };
+
+    // 备份中包含 token，仅允许当前用户读写
+    settings::write_atomic(Path::new(&path), &output, true)?;
+    Ok(manifest)
+}
+
+/// 从备份文件导入配置，dry_run 为 true 时只返回将要执行的操作
+#[tauri::command]
+pub async fn import_profile(
+    app_handle: tauri::AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    strategy: Option<ProfileConflictStrategy>,
+    dry_run: Option<bool>,
+) -> Result<ProfileImportReport, String> {
+    let data = fs::read(&path).map_err(|e| format!("读取备份文件失败: {}", e))?;
+    let archive = decrypt_archive(&data, passphrase.as_deref())?;
+    let (manifest, files) = verify_archive(read_archive(&archive)?)?;
+
+    let dry_run = dry_run.unwrap_or(false);
+    let items = apply_profile(&app_handle, &files, strategy.unwrap_or_default(), dry_run)?;
+
+    Ok(ProfileImportReport {
+        manifest,
+        dry_run,
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_profile() -> (ProfileManifest, Vec<(String, Vec<u8>)>) {
+        let files = vec![
+            (
+                <Vec<CustomTunnel>>::FILE_NAME.to_string(),
+                br#"{"version":1,"data":[]}"#.to_vec(),
+            ),
+            ("z_home.ini".to_string(), b"[common]\n".to_vec()),
+            (
+                format!("{}/bg.png", BACKGROUND_DIR),
+                vec![0x89, 0x50, 0x4e, 0x47],
+            ),
+        ];
+        let manifest = ProfileManifest {
+            format_version: PROFILE_FORMAT_VERSION,
+            app_version: "test".to_string(),
+            created_at: "2024-01-01T00:00:00+08:00".to_string(),
+            files: files
+                .iter()
+                .map(|(name, content)| ProfileManifestEntry {
+                    path: name.clone(),
+                    size: content.len() as u64,
+                    sha256: sha256_hex(content),
+                })
+                .collect(),
+        };
+        (manifest, files)
+    }
+
+    #[test]
+    fn allowed_paths() {
+        assert!(is_allowed_path("custom_tunnels.json"));
+        assert!(is_allowed_path("z_home.toml"));
+        assert!(is_allowed_path("backgrounds/bg.png"));
+
+        assert!(!is_allowed_path("process_guard.json"));
+        assert!(!is_allowed_path("z_../secret"));
+        assert!(!is_allowed_path("backgrounds/../z_home.ini"));
+        assert!(!is_allowed_path("backgrounds/"));
+        assert!(!is_allowed_path("z_C:\\evil.ini"));
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let (manifest, files) = sample_profile();
+        let archive = build_archive(&manifest, &files).unwrap();
+
+        let (restored, verified) =
+            verify_archive(read_archive(&decrypt_archive(&archive, None).unwrap()).unwrap())
+                .unwrap();
+        assert_eq!(restored.files.len(), files.len());
+        for (name, content) in &files {
+            assert_eq!(verified.get(name), Some(content), "{}", name);
+        }
+    }
+
+    #[test]
+    fn encrypted_profile_requires_passphrase() {
+        let (manifest, files) = sample_profile();
+        let archive = build_archive(&manifest, &files).unwrap();
+        let encrypted = encrypt_archive(&archive, "correct horse").unwrap();
+        assert!(encrypted.starts_with(ENCRYPTED_MAGIC));
+
+        assert!(decrypt_archive(&encrypted, None).is_err());
+        assert!(decrypt_archive(&encrypted, Some("")).is_err());
+        assert!(decrypt_archive(&encrypted, Some("wrong")).is_err());
+        assert_eq!(
+            decrypt_archive(&encrypted, Some("correct horse")).unwrap(),
+            archive
+        );
+    }
+
+    #[test]
+    fn tampered_file_fails_verification() {
+        let (manifest, mut files) = sample_profile();
+        files[1].1 = b"[common]\ntoken = stolen\n".to_vec();
+        let archive = build_archive(&manifest, &files).unwrap();
+
+        let err = verify_archive(read_archive(&archive).unwrap()).unwrap_err();
+        assert!(err.contains("z_home.ini"), "{}", err);
+    }
+
+    #[test]
+    fn manifest_with_disallowed_path_is_rejected() {
+        let (mut manifest, mut files) = sample_profile();
+        files.push(("../outside.json".to_string(), b"{}".to_vec()));
+        manifest.files.push(ProfileManifestEntry {
+            path: "../outside.json".to_string(),
+            size: 2,
+            sha256: sha256_hex(b"{}"),
+        });
+        let archive = build_archive(&manifest, &files).unwrap();
+
+        assert!(verify_archive(read_archive(&archive).unwrap()).is_err());
+    }
+}
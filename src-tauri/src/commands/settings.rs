@@ -0,0 +1,558 @@
+use crate::commands::custom_tunnel::CustomTunnel;
+use crate::commands::{download_mirror, guard_rules};
+use crate::models::{
+    AutoStartEntry, AutoStartStore, DownloadMirrors, FrpcVersionPins, GuardRule,
+    PersistedGuardState, TunnelConfig, TunnelKind,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+// 串行化所有设置文件的读-改-写，避免并发命令互相覆盖
+static SETTINGS_LOCK: Mutex<()> = Mutex::new(());
+
+/// 设置文件的结构定义
+///
+/// 文件在磁盘上保存为 `{"version": n, "data": ...}`，旧版文件没有外层结构，视为版本 0。
+/// 保险库有自己的加密格式和版本，PID 文件按隧道分别记录运行时信息，二者不使用此结构。
+pub trait SettingsSchema: Serialize + DeserializeOwned {
+    /// 设置名称，用于变更通知
+    const KEY: &'static str;
+    const FILE_NAME: &'static str;
+    const VERSION: u32;
+    /// 文件中包含 token 等敏感信息时仅允许当前用户读写
+    const PRIVATE: bool = false;
+
+    fn default_value() -> Self;
+
+    /// 将 `version` 版本的数据迁移到下一个版本
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct SettingsEnvelope<T> {
+    version: u32,
+    data: T,
+}
+
+#[derive(Serialize, Clone)]
+struct SettingsChanged {
+    key: &'static str,
+}
+
+#[derive(Serialize, Clone)]
+struct SettingsRecovered {
+    key: &'static str,
+    backup_path: String,
+    error: String,
+}
+
+fn default_stop_grace_secs() -> u64 {
+    5
+}
+
+/// 启动器的通用设置（auto_start_tunnels.json）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LauncherSettings {
+    #[serde(default)]
+    pub auto_start_tunnels: bool,
+    // 停止隧道时等待 frpc 自行退出的时间（秒）
+    #[serde(default = "default_stop_grace_secs")]
+    pub stop_grace_secs: u64,
+}
+
+impl Default for LauncherSettings {
+    fn default() -> Self {
+        Self {
+            auto_start_tunnels: false,
+            stop_grace_secs: default_stop_grace_secs(),
+        }
+    }
+}
+
+impl SettingsSchema for LauncherSettings {
+    const KEY: &'static str = "launcher";
+    const FILE_NAME: &'static str = "auto_start_tunnels.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        Self::default()
+    }
+
+    // 版本 0: {"enabled": bool}
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            0 => Ok(serde_json::json!({
+                "auto_start_tunnels": data.get("enabled").and_then(|v| v.as_bool()).unwrap_or(false),
+            })),
+            _ => Err(format!("未知的设置版本: {}", version)),
+        }
+    }
+}
+
+impl SettingsSchema for AutoStartStore {
+    const KEY: &'static str = "tunnel_auto_start";
+    const FILE_NAME: &'static str = "tunnel_auto_start.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        AutoStartStore {
+            entries: Vec::new(),
+        }
+    }
+
+    // 版本 0: {"api_123": true, "custom_xxx": true}，类型前缀不含下划线，
+    // 因此按第一个下划线拆分即可保留 id 中的下划线
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        if version != 0 {
+            return Err(format!("未知的设置版本: {}", version));
+        }
+        let serde_json::Value::Object(legacy) = data else {
+            return Err("格式无效".to_string());
+        };
+
+        let mut entries = Vec::new();
+        for (key, value) in legacy {
+            if value.as_bool() != Some(true) {
+                continue;
+            }
+            let Some((tunnel_type, tunnel_id)) = key.split_once('_') else {
+                continue;
+            };
+            let kind = match tunnel_type {
+                "api" => TunnelKind::Api,
+                "custom" => TunnelKind::Custom,
+                _ => continue,
+            };
+            entries.push(AutoStartEntry {
+                kind,
+                tunnel_id: tunnel_id.to_string(),
+                delay_ms: 0,
+                order: entries.len() as u32,
+            });
+        }
+
+        serde_json::to_value(AutoStartStore { entries }).map_err(|e| e.to_string())
+    }
+}
+
+impl SettingsSchema for Vec<CustomTunnel> {
+    const KEY: &'static str = "custom_tunnels";
+    const FILE_NAME: &'static str = "custom_tunnels.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        Vec::new()
+    }
+
+    // 版本 0 为不带外层结构的隧道数组，内容与版本 1 相同
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            0 => Ok(data),
+            _ => Err(format!("未知的设置版本: {}", version)),
+        }
+    }
+}
+
+// API 隧道最近一次启动的配置，以隧道 ID 为键，不含 token
+impl SettingsSchema for HashMap<i32, TunnelConfig> {
+    const KEY: &'static str = "api_tunnel_configs";
+    const FILE_NAME: &'static str = "api_tunnel_configs.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        HashMap::new()
+    }
+
+    // 版本 0 为不带外层结构的配置表
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            0 => Ok(data),
+            _ => Err(format!("未知的设置版本: {}", version)),
+        }
+    }
+}
+
+impl SettingsSchema for Vec<GuardRule> {
+    const KEY: &'static str = "guard_rules";
+    const FILE_NAME: &'static str = "guard_rules.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        guard_rules::default_rules()
+    }
+
+    // 版本 0 为不带外层结构的规则数组
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            0 => Ok(data),
+            _ => Err(format!("未知的设置版本: {}", version)),
+        }
+    }
+}
+
+impl SettingsSchema for PersistedGuardState {
+    const KEY: &'static str = "process_guard";
+    const FILE_NAME: &'static str = "process_guard.json";
+    const VERSION: u32 = 1;
+    const PRIVATE: bool = true;
+
+    fn default_value() -> Self {
+        Self::default()
+    }
+
+    // 版本 0 为不带外层结构的守护状态
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            0 => Ok(data),
+            _ => Err(format!("未知的设置版本: {}", version)),
+        }
+    }
+}
+
+impl SettingsSchema for FrpcVersionPins {
+    const KEY: &'static str = "frpc_versions";
+    const FILE_NAME: &'static str = "frpc_versions.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        Self::default()
+    }
+
+    fn migrate(version: u32, _data: serde_json::Value) -> Result<serde_json::Value, String> {
+        Err(format!("未知的设置版本: {}", version))
+    }
+}
+
+impl SettingsSchema for DownloadMirrors {
+    const KEY: &'static str = "download_mirrors";
+    const FILE_NAME: &'static str = "download_mirrors.json";
+    const VERSION: u32 = 1;
+
+    fn default_value() -> Self {
+        DownloadMirrors {
+            mirrors: vec![download_mirror::builtin_mirror()],
+        }
+    }
+
+    fn migrate(version: u32, _data: serde_json::Value) -> Result<serde_json::Value, String> {
+        Err(format!("未知的设置版本: {}", version))
+    }
+}
+
+/// 设置文件在应用数据目录中的路径
+pub fn get_settings_path<T: SettingsSchema>(
+    app_handle: &tauri::AppHandle,
+) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(T::FILE_NAME))
+        .map_err(|e| format!("获取应用数据目录失败: {}", e))
+}
+
+/// 先写入临时文件再重命名，保证文件内容不会因中途退出而损坏
+pub fn write_atomic(path: &Path, content: &[u8], private: bool) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建应用数据目录失败: {}", e))?;
+    }
+
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let write_tmp = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&tmp_path)?;
+        #[cfg(unix)]
+        if private {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(content)?;
+        file.sync_all()
+    };
+
+    #[cfg(not(unix))]
+    let _ = private;
+
+    if let Err(e) = write_tmp() {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!("写入配置文件失败: {}", e));
+    }
+
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("保存配置文件失败: {}", e)
+    })
+}
+
+pub fn write_json_atomic<T: Serialize>(
+    path: &Path,
+    value: &T,
+    private: bool,
+) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(value).map_err(|e| format!("序列化配置失败: {}", e))?;
+    write_atomic(path, content.as_bytes(), private)
+}
+
+enum DecodeError {
+    // 由更新版本的启动器写入，不能当作损坏处理，否则降级会丢失设置
+    Newer(String),
+    Corrupt(String),
+}
+
+// 拆出外层结构并依次迁移到当前版本
+fn decode<T: SettingsSchema>(content: &str) -> Result<(T, bool), DecodeError> {
+    let corrupt = |e: String| DecodeError::Corrupt(e);
+    let raw: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| corrupt(format!("解析失败: {}", e)))?;
+
+    let is_envelope = raw.get("version").is_some_and(|v| v.is_u64()) && raw.get("data").is_some();
+    let (mut version, mut data) = if is_envelope {
+        let envelope: SettingsEnvelope<serde_json::Value> =
+            serde_json::from_value(raw).map_err(|e| corrupt(format!("解析失败: {}", e)))?;
+        (envelope.version, envelope.data)
+    } else {
+        (0, raw)
+    };
+
+    if version > T::VERSION {
+        return Err(DecodeError::Newer(format!(
+            "配置版本 {} 高于当前支持的版本 {}，请升级启动器",
+            version,
+            T::VERSION
+        )));
+    }
+
+    let migrated = version < T::VERSION;
+    while version < T::VERSION {
+        data = T::migrate(version, data).map_err(|e| corrupt(format!("迁移失败: {}", e)))?;
+        version += 1;
+    }
+
+    let value = serde_json::from_value(data).map_err(|e| corrupt(format!("解析失败: {}", e)))?;
+    Ok((value, migrated))
+}
+
+// 损坏的文件改名备份，之后按默认值继续运行
+fn backup_corrupt_file(path: &Path) -> Result<PathBuf, String> {
+    let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+    let mut backup_name = path.as_os_str().to_owned();
+    backup_name.push(format!(".corrupt-{}", timestamp));
+    let backup_path = PathBuf::from(backup_name);
+
+    fs::rename(path, &backup_path).map_err(|e| format!("备份损坏的配置文件失败: {}", e))?;
+    Ok(backup_path)
+}
+
+fn recover_corrupt_file<T: SettingsSchema>(
+    app_handle: &tauri::AppHandle,
+    path: &Path,
+    error: &str,
+) -> Result<(), String> {
+    let backup_path = backup_corrupt_file(path)?;
+    eprintln!(
+        "[设置] {} 已损坏 ({})，已备份至 {}",
+        T::FILE_NAME,
+        error,
+        backup_path.display()
+    );
+
+    let _ = app_handle.emit(
+        "settings-recovered",
+        SettingsRecovered {
+            key: T::KEY,
+            backup_path: backup_path.to_string_lossy().to_string(),
+            error: error.to_string(),
+        },
+    );
+    Ok(())
+}
+
+fn load_unlocked<T: SettingsSchema>(app_handle: &tauri::AppHandle) -> Result<T, String> {
+    let path = get_settings_path::<T>(app_handle)?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default_value()),
+        Err(e) => return Err(format!("读取配置文件失败: {}", e)),
+    };
+
+    match decode::<T>(&content) {
+        Ok((value, migrated)) => {
+            // 迁移后立即写回新格式
+            if migrated {
+                write_unlocked(app_handle, &value)?;
+            }
+            Ok(value)
+        }
+        Err(DecodeError::Newer(e)) => Err(format!("{}: {}", T::FILE_NAME, e)),
+        Err(DecodeError::Corrupt(e)) => {
+            recover_corrupt_file::<T>(app_handle, &path, &e)?;
+            Ok(T::default_value())
+        }
+    }
+}
+
+fn write_unlocked<T: SettingsSchema>(
+    app_handle: &tauri::AppHandle,
+    value: &T,
+) -> Result<(), String> {
+    write_envelope(&get_settings_path::<T>(app_handle)?, value)
+}
+
+fn write_envelope<T: SettingsSchema>(path: &Path, value: &T) -> Result<(), String> {
+    let envelope = SettingsEnvelope {
+        version: T::VERSION,
+        data: value,
+    };
+    write_json_atomic(path, &envelope, T::PRIVATE)
+}
+
+/// 解析其他来源（如备份文件）中的设置内容，并迁移到当前版本
+pub fn parse<T: SettingsSchema>(content: &str) -> Result<T, String> {
+    match decode::<T>(content) {
+        Ok((value, _)) => Ok(value),
+        Err(DecodeError::Newer(e)) | Err(DecodeError::Corrupt(e)) => {
+            Err(format!("{}: {}", T::FILE_NAME, e))
+        }
+    }
+}
+
+/// 读取设置，文件不存在时返回默认值
+pub fn load<T: SettingsSchema>(app_handle: &tauri::AppHandle) -> Result<T, String> {
+    let _lock = SETTINGS_LOCK
+        .lock()
+        .map_err(|e| format!("获取设置锁失败: {}", e))?;
+    load_unlocked(app_handle)
+}
+
+/// 保存启动器内部使用的状态，不通知前端
+pub fn save<T: SettingsSchema>(app_handle: &tauri::AppHandle, value: &T) -> Result<(), String> {
+    save_at(&get_settings_path::<T>(app_handle)?, value)
+}
+
+/// 保存到 get_settings_path 预先取得的路径，供无法获取 AppHandle 的调用方使用
+pub fn save_at<T: SettingsSchema>(path: &Path, value: &T) -> Result<(), String> {
+    let _lock = SETTINGS_LOCK
+        .lock()
+        .map_err(|e| format!("获取设置锁失败: {}", e))?;
+    write_envelope(path, value)
+}
+
+/// 在锁内读取、修改并保存设置，保存后通知前端；修改函数返回错误时不写入
+pub fn update<T, R, F>(app_handle: &tauri::AppHandle, f: F) -> Result<R, String>
+where
+    T: SettingsSchema,
+    F: FnOnce(&mut T) -> Result<R, String>,
+{
+    let result = {
+        let _lock = SETTINGS_LOCK
+            .lock()
+            .map_err(|e| format!("获取设置锁失败: {}", e))?;
+        let mut value = load_unlocked::<T>(app_handle)?;
+        let result = f(&mut value)?;
+        write_unlocked(app_handle, &value)?;
+        result
+    };
+
+    let _ = app_handle.emit("settings-changed", SettingsChanged { key: T::KEY });
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_ok<T: SettingsSchema>(content: &str) -> (T, bool) {
+        match decode::<T>(content) {
+            Ok(decoded) => decoded,
+            Err(DecodeError::Newer(e)) | Err(DecodeError::Corrupt(e)) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn migrates_legacy_launcher_settings() {
+        let (settings, migrated) = decode_ok::<LauncherSettings>(r#"{"enabled": true}"#);
+        assert!(migrated);
+        assert!(settings.auto_start_tunnels);
+        assert_eq!(settings.stop_grace_secs, 5);
+    }
+
+    #[test]
+    fn migrates_legacy_auto_start_map() {
+        let content =
+            r#"{"api_12": true, "custom_my_tunnel": true, "api_13": false, "other_1": true}"#;
+        let (store, migrated) = decode_ok::<AutoStartStore>(content);
+        assert!(migrated);
+
+        let mut entries: Vec<_> = store
+            .entries
+            .iter()
+            .map(|entry| (entry.kind, entry.tunnel_id.as_str()))
+            .collect();
+        entries.sort_by_key(|(_, id)| id.to_string());
+        assert_eq!(
+            entries,
+            [(TunnelKind::Api, "12"), (TunnelKind::Custom, "my_tunnel")]
+        );
+    }
+
+    #[test]
+    fn reads_current_envelope_without_migration() {
+        let content = r#"{"version": 1, "data": {"entries": [
+            {"kind": "custom", "tunnel_id": "a", "delay_ms": 1500, "order": 2}
+        ]}}"#;
+        let (store, migrated) = decode_ok::<AutoStartStore>(content);
+        assert!(!migrated);
+        assert_eq!(store.entries.len(), 1);
+        assert_eq!(store.entries[0].delay_ms, 1500);
+        assert_eq!(store.entries[0].order, 2);
+    }
+
+    #[test]
+    fn newer_version_is_not_treated_as_corrupt() {
+        let result = decode::<LauncherSettings>(r#"{"version": 99, "data": {}}"#);
+        assert!(matches!(result, Err(DecodeError::Newer(_))));
+    }
+
+    #[test]
+    fn invalid_content_is_corrupt() {
+        for content in [
+            "{not json",
+            r#"{"version": 1, "data": {"entries": 3}}"#,
+            "[1, 2]",
+        ] {
+            let result = decode::<AutoStartStore>(content);
+            assert!(
+                matches!(result, Err(DecodeError::Corrupt(_))),
+                "{}",
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn corrupt_file_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("chmlfrp-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(AutoStartStore::FILE_NAME);
+        fs::write(&path, "{not json").unwrap();
+
+        let backup_path = backup_corrupt_file(&path).unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read_to_string(&backup_path).unwrap(), "{not json");
+        assert!(backup_path
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("tunnel_auto_start.json.corrupt-"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
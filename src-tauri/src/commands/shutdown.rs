@@ -0,0 +1,83 @@
+use crate::commands::{frpc_config, process_stop, tunnel_log};
+use crate::models::{FrpcProcesses, TunnelKey};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::Manager;
+
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+static SHUTDOWN_FINISHED: AtomicBool = AtomicBool::new(false);
+
+// 退出流程开始后，守护进程不再重启隧道
+pub fn is_shutting_down() -> bool {
+    SHUTDOWN_STARTED.load(Ordering::SeqCst)
+}
+
+pub fn is_shutdown_finished() -> bool {
+    SHUTDOWN_FINISHED.load(Ordering::SeqCst)
+}
+
+fn collect_running_tunnels(processes: &FrpcProcesses) -> Vec<TunnelKey> {
+    let mut tunnel_keys: Vec<TunnelKey> = processes
+        .processes
+        .lock()
+        .map(|procs| procs.keys().cloned().collect())
+        .unwrap_or_default();
+    if let Ok(adopted) = processes.adopted.lock() {
+        tunnel_keys.extend(adopted.keys().cloned());
+    }
+    tunnel_keys
+}
+
+async fn shutdown(app_handle: &tauri::AppHandle) {
+    // 直接停止进程而不经过 stop_frpc，保留守护列表以便下次启动时恢复
+    let tunnel_keys = collect_running_tunnels(&app_handle.state::<FrpcProcesses>());
+    let stops = tunnel_keys
+        .iter()
+        .map(|tunnel_key| process_stop::stop_tunnel_process(app_handle, tunnel_key));
+    futures_util::future::join_all(stops).await;
+
+    if let Ok(app_dir) = app_handle.path().app_data_dir() {
+        frpc_config::cleanup_generated_configs(&app_dir, &HashSet::new());
+    }
+
+    tunnel_log::flush_all(app_handle);
+}
+
+// 停止所有隧道、清理生成的配置并写完日志后退出，重复调用会被忽略
+pub fn request_shutdown(app_handle: &tauri::AppHandle, exit_code: i32) {
+    if SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        shutdown(&app_handle).await;
+        SHUTDOWN_FINISHED.store(true, Ordering::SeqCst);
+        app_handle.exit(exit_code);
+    });
+}
+
+// 收到 SIGTERM、SIGINT 或 SIGHUP 时走正常退出流程
+#[cfg(unix)]
+pub fn listen_for_signals(app_handle: tauri::AppHandle) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tauri::async_runtime::spawn(async move {
+        let (Ok(mut terminate), Ok(mut interrupt), Ok(mut hangup)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::interrupt()),
+            signal(SignalKind::hangup()),
+        ) else {
+            eprintln!("[错误] 注册退出信号监听失败");
+            return;
+        };
+
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+            _ = hangup.recv() => {}
+        }
+
+        request_shutdown(&app_handle, 0);
+    });
+}
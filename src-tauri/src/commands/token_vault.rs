@@ -0,0 +1,295 @@
+use crate::commands::{crypto, settings};
+use crate::models::{TunnelConfig, TunnelKey};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+const VAULT_FILE: &str = "token_vault.json";
+const VAULT_KEY_FILE: &str = "token_vault.key";
+const VAULT_VERSION: u32 = 1;
+
+pub const USER_TOKEN_ENV: &str = "CHMLFRP_USER_TOKEN";
+pub const NODE_TOKEN_ENV: &str = "CHMLFRP_NODE_TOKEN";
+
+// 串行化保险库的读-改-写
+static VAULT_LOCK: Mutex<()> = Mutex::new(());
+// 派生密钥较慢，按盐值缓存
+static VAULT_KEY: Mutex<Option<(Vec<u8>, [u8; crypto::KEY_LEN])>> = Mutex::new(None);
+
+// 保险库文件，data 为加密后的 {条目名: [密钥...]}
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    data: String,
+}
+
+type VaultEntries = HashMap<String, Vec<String>>;
+
+fn get_app_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用目录失败: {}", e))
+}
+
+// 本地随机密钥文件，作为系统钥匙串的替代；仅在文件不存在时生成，已有的密钥文件不会被覆盖
+fn load_local_secret(app_dir: &std::path::Path) -> Result<Vec<u8>, String> {
+    let key_path = app_dir.join(VAULT_KEY_FILE);
+    match std::fs::read(&key_path) {
+        Ok(secret) if secret.len() == crypto::KEY_LEN => Ok(secret),
+        Ok(_) => Err(format!("保险库密钥文件已损坏: {}", key_path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let secret = crypto::random_bytes::<{ crypto::KEY_LEN }>().to_vec();
+            settings::write_atomic(&key_path, &secret, true)?;
+            Ok(secret)
+        }
+        Err(e) => Err(format!("读取保险库密钥失败: {}", e)),
+    }
+}
+
+fn vault_key(app_dir: &std::path::Path, salt: &[u8]) -> Result<[u8; crypto::KEY_LEN], String> {
+    let mut cached = VAULT_KEY
+        .lock()
+        .map_err(|e| format!("获取保险库锁失败: {}", e))?;
+    if let Some((cached_salt, key)) = cached.as_ref() {
+        if cached_salt == salt {
+            return Ok(*key);
+        }
+    }
+
+    let secret = load_local_secret(app_dir)?;
+    let key = crypto::derive_key(&secret, salt)?;
+    *cached = Some((salt.to_vec(), key));
+    Ok(key)
+}
+
+fn empty_vault() -> (Vec<u8>, VaultEntries) {
+    (
+        crypto::random_bytes::<{ crypto::SALT_LEN }>().to_vec(),
+        HashMap::new(),
+    )
+}
+
+// 读取保险库，密钥文件不可用时返回错误且不改动保险库文件
+fn read_vault(app_dir: &std::path::Path) -> Result<(Vec<u8>, VaultEntries), String> {
+    let path = app_dir.join(VAULT_FILE);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(empty_vault());
+    };
+
+    let parsed = serde_json::from_str::<VaultFile>(&content)
+        .map_err(|e| format!("解析保险库失败: {}", e))
+        .and_then(|file| {
+            if file.version != VAULT_VERSION {
+                return Err(format!("不支持的保险库版本: {}", file.version));
+            }
+            let salt = hex::decode(&file.salt).map_err(|e| format!("解析保险库失败: {}", e))?;
+            let sealed = hex::decode(&file.data).map_err(|e| format!("解析保险库失败: {}", e))?;
+            Ok((salt, sealed))
+        });
+
+    // 密钥文件读取失败直接返回，避免把仍可解密的保险库当作损坏处理
+    let decoded = match parsed {
+        Ok((salt, sealed)) => {
+            let key = vault_key(app_dir, &salt)?;
+            crypto::open(&key, &sealed).and_then(|plaintext| {
+                let entries = serde_json::from_slice::<VaultEntries>(&plaintext)
+                    .map_err(|e| format!("解析保险库失败: {}", e))?;
+                Ok((salt, entries))
+            })
+        }
+        Err(e) => Err(e),
+    };
+
+    decoded.or_else(|e| {
+        // 无法解密时保留原文件，重新开始一个空的保险库
+        let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+        let backup = app_dir.join(format!("{}.corrupt-{}", VAULT_FILE, timestamp));
+        let _ = std::fs::rename(&path, &backup);
+        eprintln!("[保险库] {}，已备份至 {}", e, backup.display());
+        Ok(empty_vault())
+    })
+}
+
+fn write_vault(
+    app_dir: &std::path::Path,
+    salt: &[u8],
+    entries: &VaultEntries,
+) -> Result<(), String> {
+    let key = vault_key(app_dir, salt)?;
+    let plaintext = serde_json::to_vec(entries).map_err(|e| format!("序列化保险库失败: {}", e))?;
+    let file = VaultFile {
+        version: VAULT_VERSION,
+        salt: hex::encode(salt),
+        data: hex::encode(crypto::seal(&key, &plaintext)?),
+    };
+    settings::write_json_atomic(&app_dir.join(VAULT_FILE), &file, true)
+}
+
+fn update_vault(
+    app_handle: &tauri::AppHandle,
+    f: impl FnOnce(&mut VaultEntries) -> bool,
+) -> Result<(), String> {
+    let app_dir = get_app_dir(app_handle)?;
+    let _lock = VAULT_LOCK
+        .lock()
+        .map_err(|e| format!("获取保险库锁失败: {}", e))?;
+
+    let (salt, mut entries) = read_vault(&app_dir)?;
+    if f(&mut entries) {
+        write_vault(&app_dir, &salt, &entries)?;
+    }
+    Ok(())
+}
+
+pub fn api_entry(tunnel_id: i32) -> String {
+    format!("api:{}", tunnel_id)
+}
+
+pub fn custom_entry(tunnel_id: &str) -> String {
+    format!("custom:{}", tunnel_id)
+}
+
+/// 保存一组密钥，内容未变化时不写盘
+pub fn store_secrets(
+    app_handle: &tauri::AppHandle,
+    entry: &str,
+    secrets: Vec<String>,
+) -> Result<(), String> {
+    update_vault(app_handle, |entries| {
+        if entries.get(entry) == Some(&secrets) {
+            return false;
+        }
+        entries.insert(entry.to_string(), secrets);
+        true
+    })
+}
+
+fn read_entries(app_handle: &tauri::AppHandle) -> VaultEntries {
+    let result = get_app_dir(app_handle).and_then(|app_dir| {
+        let _lock = VAULT_LOCK
+            .lock()
+            .map_err(|e| format!("获取保险库锁失败: {}", e))?;
+        read_vault(&app_dir)
+    });
+    match result {
+        Ok((_, entries)) => entries,
+        Err(e) => {
+            eprintln!("[保险库] {}", e);
+            HashMap::new()
+        }
+    }
+}
+
+pub fn load_secrets(app_handle: &tauri::AppHandle, entry: &str) -> Vec<String> {
+    read_entries(app_handle).remove(entry).unwrap_or_default()
+}
+
+/// 接管已运行的进程时，读取该隧道用于日志脱敏的 token
+pub fn tunnel_secrets(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey) -> Vec<String> {
+    let entry = match tunnel_key {
+        TunnelKey::Api(tunnel_id) => api_entry(*tunnel_id),
+        TunnelKey::Custom(tunnel_id) => custom_entry(tunnel_id),
+    };
+    load_secrets(app_handle, &entry)
+}
+
+pub fn remove_secrets(app_handle: &tauri::AppHandle, entry: &str) {
+    if let Err(e) = update_vault(app_handle, |entries| entries.remove(entry).is_some()) {
+        eprintln!("[保险库] 删除密钥失败: {}", e);
+    }
+}
+
+/// frpc 配置模板中引用环境变量的写法
+pub fn env_placeholder(name: &str) -> String {
+    format!("{{{{ .Envs.{} }}}}", name)
+}
+
+/// 生成配置文件时使用的副本，token 由环境变量传入
+pub fn with_token_placeholders(config: &TunnelConfig) -> TunnelConfig {
+    let mut config = config.clone();
+    config.user_token = env_placeholder(USER_TOKEN_ENV);
+    config.node_token = env_placeholder(NODE_TOKEN_ENV);
+    config
+}
+
+pub fn store_api_tokens(app_handle: &tauri::AppHandle, config: &TunnelConfig) {
+    if config.user_token.is_empty() && config.node_token.is_empty() {
+        return;
+    }
+    let secrets = vec![config.user_token.clone(), config.node_token.clone()];
+    if let Err(e) = store_secrets(app_handle, &api_entry(config.tunnel_id), secrets) {
+        eprintln!(
+            "[保险库] 保存隧道 {} 的 token 失败: {}",
+            config.tunnel_id, e
+        );
+    }
+}
+
+/// 从保险库补全磁盘上读取的配置中的 token
+pub fn fill_api_tokens(app_handle: &tauri::AppHandle, config: &mut TunnelConfig) {
+    if !config.user_token.is_empty() && !config.node_token.is_empty() {
+        return;
+    }
+    let mut secrets = load_secrets(app_handle, &api_entry(config.tunnel_id)).into_iter();
+    if let (Some(user_token), Some(node_token)) = (secrets.next(), secrets.next()) {
+        config.user_token = user_token;
+        config.node_token = node_token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_app_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("chmlfrp-vault-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn vault_round_trip() {
+        let dir = temp_app_dir("round-trip");
+        let (salt, mut entries) = read_vault(&dir).unwrap();
+        assert!(entries.is_empty());
+
+        entries.insert(api_entry(12), vec!["user".into(), "node".into()]);
+        entries.insert(custom_entry("home"), vec!["secret".into()]);
+        write_vault(&dir, &salt, &entries).unwrap();
+
+        // 文件中只有密文
+        let raw = std::fs::read_to_string(dir.join(VAULT_FILE)).unwrap();
+        assert!(!raw.contains("secret") && !raw.contains("node"));
+
+        let (read_salt, read_entries) = read_vault(&dir).unwrap();
+        assert_eq!(read_salt, salt);
+        assert_eq!(read_entries, entries);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn vault_key_file_is_kept() {
+        let dir = temp_app_dir("key-file");
+        let (salt, entries) = read_vault(&dir).unwrap();
+        write_vault(&dir, &salt, &entries).unwrap();
+        let key = std::fs::read(dir.join(VAULT_KEY_FILE)).unwrap();
+
+        // 密钥文件损坏时报错，不生成新密钥，也不移走保险库
+        std::fs::write(dir.join(VAULT_KEY_FILE), b"short").unwrap();
+        *VAULT_KEY.lock().unwrap() = None;
+        assert!(read_vault(&dir).is_err());
+        assert!(dir.join(VAULT_FILE).exists());
+
+        std::fs::write(dir.join(VAULT_KEY_FILE), &key).unwrap();
+        assert!(read_vault(&dir).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -18,6 +18,6 @@ pub fn show_window<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn quit_app<R: Runtime>(app: AppHandle<R>) {
-    app.exit(0);
+pub fn quit_app(app: AppHandle) {
+    crate::commands::shutdown::request_shutdown(&app, 0);
 }
@@ -0,0 +1,31 @@
+use crate::commands::{settings, token_vault};
+use crate::models::TunnelConfig;
+use std::collections::HashMap;
+
+// 记录 API 隧道最近一次成功启动的配置，供无界面启动时使用
+pub fn cache_api_config(app_handle: &tauri::AppHandle, config: &TunnelConfig) {
+    let result = settings::update(app_handle, |cache: &mut HashMap<i32, TunnelConfig>| {
+        cache.insert(config.tunnel_id, config.without_tokens());
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("[隧道配置] 缓存隧道 {} 的配置失败: {}", config.tunnel_id, e);
+    }
+}
+
+pub fn get_cached_api_config(
+    app_handle: &tauri::AppHandle,
+    tunnel_id: i32,
+) -> Option<TunnelConfig> {
+    match settings::load::<HashMap<i32, TunnelConfig>>(app_handle) {
+        Ok(mut cache) => {
+            let mut config = cache.remove(&tunnel_id)?;
+            token_vault::fill_api_tokens(app_handle, &mut config);
+            Some(config)
+        }
+        Err(e) => {
+            eprintln!("[隧道配置] 读取隧道配置缓存失败: {}", e);
+            None
+        }
+    }
+}
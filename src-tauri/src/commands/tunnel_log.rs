@@ -0,0 +1,398 @@
+use crate::models::{TunnelKey, TunnelLogFile, TunnelLogPage, TunnelLogWriter, TunnelLogs};
+use std::collections::hash_map::Entry;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tauri::{Manager, State};
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE_PREFIX: &str = "tunnel_";
+const LOG_FILE_EXT: &str = "log";
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: u32 = 5;
+const ROTATE_INTERVAL_HOURS: i64 = 24;
+const LOG_RETENTION_DAYS: i64 = 7;
+const DEFAULT_PAGE_SIZE: usize = 500;
+// 日志文件的第一行，记录文件开始写入的时间，用于按时间轮转
+const LOG_HEADER_PREFIX: &str = "# ChmlFrpLauncher log started at ";
+
+fn get_log_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(LOG_DIR))
+        .map_err(|e| format!("获取应用目录失败: {}", e))
+}
+
+// tunnel_<key>.log 为当前文件，tunnel_<key>.<n>.log 为轮转后的历史文件
+fn log_file_name(tunnel_key: &TunnelKey, index: u32) -> String {
+    if index == 0 {
+        format!("{}{}.{}", LOG_FILE_PREFIX, tunnel_key, LOG_FILE_EXT)
+    } else {
+        format!(
+            "{}{}.{}.{}",
+            LOG_FILE_PREFIX, tunnel_key, index, LOG_FILE_EXT
+        )
+    }
+}
+
+fn parse_log_file_name(file_name: &str) -> Option<(TunnelKey, u32)> {
+    let stem = file_name
+        .strip_prefix(LOG_FILE_PREFIX)?
+        .strip_suffix(LOG_FILE_EXT)?
+        .strip_suffix('.')?;
+
+    match stem.split_once('.') {
+        Some((id, index)) => Some((id.parse().ok()?, index.parse().ok()?)),
+        None => Some((stem.parse().ok()?, 0)),
+    }
+}
+
+fn modified_time(path: &Path) -> Option<chrono::DateTime<chrono::Local>> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(chrono::DateTime::<chrono::Local>::from)
+}
+
+fn open_writer(log_dir: &Path, tunnel_key: &TunnelKey) -> Result<TunnelLogWriter, String> {
+    fs::create_dir_all(log_dir).map_err(|e| format!("创建日志目录失败: {}", e))?;
+
+    let path = log_dir.join(log_file_name(tunnel_key, 0));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开日志文件失败: {}", e))?;
+
+    let mut size = file
+        .metadata()
+        .map_err(|e| format!("读取日志文件信息失败: {}", e))?
+        .len();
+
+    // 文件系统的创建时间不可靠，新文件写入文件头记录开始时间
+    let created_at = if size == 0 {
+        let now = chrono::Local::now();
+        let header = format!("{}{}\n", LOG_HEADER_PREFIX, now.to_rfc3339());
+        file.write_all(header.as_bytes())
+            .map_err(|e| format!("写入日志文件失败: {}", e))?;
+        size = header.len() as u64;
+        now
+    } else {
+        read_header(&path).unwrap_or_else(chrono::Local::now)
+    };
+
+    Ok(TunnelLogWriter {
+        file,
+        size,
+        created_at,
+    })
+}
+
+fn read_header(path: &Path) -> Option<chrono::DateTime<chrono::Local>> {
+    let file = fs::File::open(path).ok()?;
+    let mut first_line = String::new();
+    BufReader::new(file).read_line(&mut first_line).ok()?;
+    let timestamp = first_line.trim_end().strip_prefix(LOG_HEADER_PREFIX)?;
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|time| time.with_timezone(&chrono::Local))
+}
+
+fn needs_rotation(writer: &TunnelLogWriter) -> bool {
+    writer.size >= MAX_LOG_FILE_SIZE
+        || chrono::Local::now() - writer.created_at
+            >= chrono::Duration::hours(ROTATE_INTERVAL_HOURS)
+}
+
+fn rotate_files(log_dir: &Path, tunnel_key: &TunnelKey) {
+    let _ = fs::remove_file(log_dir.join(log_file_name(tunnel_key, MAX_ROTATED_FILES)));
+
+    for index in (0..MAX_ROTATED_FILES).rev() {
+        let from = log_dir.join(log_file_name(tunnel_key, index));
+        if from.exists() {
+            let _ = fs::rename(&from, log_dir.join(log_file_name(tunnel_key, index + 1)));
+        }
+    }
+}
+
+/// 删除超过保留期限的历史日志
+pub fn cleanup_expired_logs(app_handle: &tauri::AppHandle) {
+    let Ok(log_dir) = get_log_dir(app_handle) else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(&log_dir) else {
+        return;
+    };
+
+    let expire_before = chrono::Local::now() - chrono::Duration::days(LOG_RETENTION_DAYS);
+
+    for entry in entries.flatten() {
+        let Ok(file_name) = entry.file_name().into_string() else {
+            continue;
+        };
+        if parse_log_file_name(&file_name).is_none() {
+            continue;
+        }
+        if modified_time(&entry.path()).is_some_and(|m| m < expire_before) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// 追加一行日志到隧道日志文件，必要时轮转
+pub fn append_line(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    timestamp: &str,
+    message: &str,
+) {
+    let Ok(log_dir) = get_log_dir(app_handle) else {
+        return;
+    };
+    let logs = app_handle.state::<TunnelLogs>();
+    let Ok(mut writers) = logs.writers.lock() else {
+        return;
+    };
+
+    if writers.get(tunnel_key).is_some_and(needs_rotation) {
+        writers.remove(tunnel_key);
+        rotate_files(&log_dir, tunnel_key);
+    }
+
+    let writer = match writers.entry(tunnel_key.clone()) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => match open_writer(&log_dir, tunnel_key) {
+            Ok(writer) => entry.insert(writer),
+            Err(e) => {
+                eprintln!("[日志] {}", e);
+                return;
+            }
+        },
+    };
+
+    let line = format!("{} {}\n", timestamp, message);
+    if writer.file.write_all(line.as_bytes()).is_ok() {
+        writer.size += line.len() as u64;
+    } else {
+        writers.remove(tunnel_key);
+    }
+}
+
+// 退出前把所有日志写入磁盘并关闭文件
+pub fn flush_all(app_handle: &tauri::AppHandle) {
+    let logs = app_handle.state::<TunnelLogs>();
+    let Ok(mut writers) = logs.writers.lock() else {
+        return;
+    };
+
+    for writer in writers.values_mut() {
+        let _ = writer.file.flush();
+        let _ = writer.file.sync_all();
+    }
+    writers.clear();
+}
+
+fn validate_log_file_name(file_name: &str) -> Result<(), String> {
+    if parse_log_file_name(file_name).is_none() {
+        return Err("无效的日志文件名".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_tunnel_logs(app_handle: tauri::AppHandle) -> Result<Vec<TunnelLogFile>, String> {
+    let log_dir = get_log_dir(&app_handle)?;
+    if !log_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&log_dir).map_err(|e| format!("读取日志目录失败: {}", e))?;
+
+    let mut files: Vec<(u32, TunnelLogFile)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            let (tunnel_key, index) = parse_log_file_name(&file_name)?;
+            let metadata = entry.metadata().ok()?;
+            let modified = modified_time(&entry.path())
+                .map(|m| m.to_rfc3339())
+                .unwrap_or_default();
+            Some((
+                index,
+                TunnelLogFile {
+                    file_name,
+                    tunnel_key,
+                    size: metadata.len(),
+                    modified,
+                },
+            ))
+        })
+        .collect();
+
+    files.sort_by(|(a_index, a), (b_index, b)| {
+        a.tunnel_key.cmp(&b.tunnel_key).then(a_index.cmp(b_index))
+    });
+
+    Ok(files.into_iter().map(|(_, file)| file).collect())
+}
+
+#[tauri::command]
+pub async fn read_tunnel_log(
+    app_handle: tauri::AppHandle,
+    file_name: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<TunnelLogPage, String> {
+    validate_log_file_name(&file_name)?;
+
+    let path = get_log_dir(&app_handle)?.join(&file_name);
+    if !path.exists() {
+        return Err("日志文件不存在".to_string());
+    }
+
+    let content = fs::read(&path).map_err(|e| format!("读取日志文件失败: {}", e))?;
+    let content = String::from_utf8_lossy(&content);
+    let all_lines: Vec<&str> = content
+        .lines()
+        .enumerate()
+        .filter(|(index, line)| *index != 0 || !line.starts_with(LOG_HEADER_PREFIX))
+        .map(|(_, line)| line)
+        .collect();
+
+    let total_lines = all_lines.len();
+    let offset = offset.unwrap_or(0).min(total_lines);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let end = offset.saturating_add(limit).min(total_lines);
+
+    Ok(TunnelLogPage {
+        file_name,
+        lines: all_lines[offset..end]
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+        offset,
+        total_lines,
+        has_more: end < total_lines,
+    })
+}
+
+#[tauri::command]
+pub async fn delete_tunnel_log(
+    app_handle: tauri::AppHandle,
+    file_name: String,
+    logs: State<'_, TunnelLogs>,
+) -> Result<(), String> {
+    validate_log_file_name(&file_name)?;
+
+    if let Some((tunnel_key, 0)) = parse_log_file_name(&file_name) {
+        if let Ok(mut writers) = logs.writers.lock() {
+            writers.remove(&tunnel_key);
+        }
+    }
+
+    let path = get_log_dir(&app_handle)?.join(&file_name);
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("删除日志文件失败: {}", e))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("chmlfrp-logs-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn log_file_names_round_trip() {
+        let api = TunnelKey::Api(42);
+        let custom = TunnelKey::Custom("home-ssh".to_string());
+        assert_eq!(log_file_name(&api, 0), "tunnel_api_42.log");
+        assert_eq!(log_file_name(&custom, 3), "tunnel_custom_home-ssh.3.log");
+        for key in [api, custom] {
+            for index in [0, 1, MAX_ROTATED_FILES] {
+                assert_eq!(
+                    parse_log_file_name(&log_file_name(&key, index)),
+                    Some((key.clone(), index))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn foreign_file_names_are_ignored() {
+        for name in [
+            "tunnel_42.log",
+            "tunnel_api_.log",
+            "tunnel_api_42",
+            "tunnel_api_42.txt",
+            "tunnel_api_42.x.log",
+            "tunnel_api_abc.log",
+            "tunnel_custom_.log",
+            "frpc_api_42.log",
+            "tunnel_custom_../42.log",
+        ] {
+            assert_eq!(parse_log_file_name(name), None, "{}", name);
+            assert!(validate_log_file_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn new_log_file_starts_with_header() {
+        let dir = temp_log_dir("header");
+        let key = TunnelKey::Api(1);
+        let writer = open_writer(&dir, &key).unwrap();
+        assert!(!needs_rotation(&writer));
+
+        let path = dir.join(log_file_name(&key, 0));
+        let written = read_header(&path).unwrap();
+        assert_eq!(written.timestamp(), writer.created_at.timestamp());
+        assert_eq!(fs::metadata(&path).unwrap().len(), writer.size);
+
+        // 重新打开已有文件时沿用文件头中的时间
+        drop(writer);
+        let reopened = open_writer(&dir, &key).unwrap();
+        assert_eq!(reopened.created_at.timestamp(), written.timestamp());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotation_shifts_files_and_drops_oldest() {
+        let dir = temp_log_dir("rotate");
+        let key = TunnelKey::Custom("web".to_string());
+        for index in 0..=MAX_ROTATED_FILES {
+            fs::write(dir.join(log_file_name(&key, index)), index.to_string()).unwrap();
+        }
+
+        rotate_files(&dir, &key);
+
+        assert!(!dir.join(log_file_name(&key, 0)).exists());
+        for index in 1..=MAX_ROTATED_FILES {
+            let content = fs::read_to_string(dir.join(log_file_name(&key, index))).unwrap();
+            assert_eq!(content, (index - 1).to_string());
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rotation_is_due_by_size_or_age() {
+        let dir = temp_log_dir("due");
+        let mut writer = open_writer(&dir, &TunnelKey::Api(9)).unwrap();
+
+        writer.size = MAX_LOG_FILE_SIZE;
+        assert!(needs_rotation(&writer));
+
+        writer.size = 0;
+        writer.created_at = chrono::Local::now() - chrono::Duration::hours(ROTATE_INTERVAL_HOURS);
+        assert!(needs_rotation(&writer));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::models::{
+    FrpcExitInfo, FrpcLogEvent, FrpcLogEventKind, TunnelKey, TunnelStatus, TunnelStatusMessage,
+    TunnelStatuses,
+};
+use tauri::{Emitter, Manager, State};
+
+pub fn get_status(statuses: &TunnelStatuses, tunnel_key: &TunnelKey) -> TunnelStatus {
+    statuses
+        .statuses
+        .lock()
+        .ok()
+        .and_then(|s| s.get(tunnel_key).cloned())
+        .unwrap_or(TunnelStatus::Stopped)
+}
+
+pub fn set_status(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey, status: TunnelStatus) {
+    let statuses = app_handle.state::<TunnelStatuses>();
+    let Ok(mut map) = statuses.statuses.lock() else {
+        return;
+    };
+
+    if map.get(tunnel_key) == Some(&status) {
+        return;
+    }
+    map.insert(tunnel_key.clone(), status.clone());
+    drop(map);
+
+    let _ = app_handle.emit(
+        "tunnel-status-changed",
+        TunnelStatusMessage {
+            tunnel_key: tunnel_key.clone(),
+            status,
+            timestamp: chrono::Local::now().format("%Y/%m/%d %H:%M:%S").to_string(),
+        },
+    );
+}
+
+fn next_status(current: &TunnelStatus, event: Option<&FrpcLogEvent>) -> Option<TunnelStatus> {
+    let Some(event) = event else {
+        // frpc 已开始输出日志，说明进程正在连接服务器
+        return match current {
+            TunnelStatus::Starting => Some(TunnelStatus::LoggingIn),
+            _ => None,
+        };
+    };
+
+    let reason = || {
+        event
+            .reason
+            .clone()
+            .unwrap_or_else(|| event.message.clone())
+    };
+
+    match event.kind {
+        FrpcLogEventKind::LoginSuccess => match current {
+            TunnelStatus::Online => None,
+            _ => Some(TunnelStatus::LoggingIn),
+        },
+        FrpcLogEventKind::ProxyStarted => Some(TunnelStatus::Online),
+        FrpcLogEventKind::LoginFailed | FrpcLogEventKind::ProxyStartFailed => {
+            Some(TunnelStatus::Failed { reason: reason() })
+        }
+        FrpcLogEventKind::ConnectFailed => match current {
+            TunnelStatus::Online | TunnelStatus::Reconnecting => Some(TunnelStatus::Reconnecting),
+            _ => Some(TunnelStatus::Failed { reason: reason() }),
+        },
+        FrpcLogEventKind::Reconnecting => Some(TunnelStatus::Reconnecting),
+    }
+}
+
+pub fn apply_log_line(
+    app_handle: &tauri::AppHandle,
+    tunnel_key: &TunnelKey,
+    event: Option<&FrpcLogEvent>,
+) {
+    let current = get_status(&app_handle.state::<TunnelStatuses>(), tunnel_key);
+    if current == TunnelStatus::Stopped {
+        return;
+    }
+
+    if let Some(status) = next_status(&current, event) {
+        set_status(app_handle, tunnel_key, status);
+    }
+}
+
+// 进程退出后保留失败原因，否则标记为已停止
+pub fn mark_process_exited(app_handle: &tauri::AppHandle, tunnel_key: &TunnelKey) {
+    let current = get_status(&app_handle.state::<TunnelStatuses>(), tunnel_key);
+    if !matches!(current, TunnelStatus::Failed { .. }) {
+        set_status(app_handle, tunnel_key, TunnelStatus::Stopped);
+    }
+}
+
+#[tauri::command]
+pub async fn get_tunnel_status(
+    tunnel_key: TunnelKey,
+    statuses: State<'_, TunnelStatuses>,
+) -> Result<TunnelStatus, String> {
+    Ok(get_status(&statuses, &tunnel_key))
+}
+
+#[tauri::command]
+pub async fn get_tunnel_exit_info(
+    tunnel_key: TunnelKey,
+    statuses: State<'_, TunnelStatuses>,
+) -> Result<Option<FrpcExitInfo>, String> {
+    let exits = statuses
+        .exits
+        .lock()
+        .map_err(|e| format!("获取隧道状态锁失败: {}", e))?;
+    Ok(exits.get(&tunnel_key).cloned())
+}
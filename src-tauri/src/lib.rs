@@ -2,7 +2,10 @@ mod commands;
 mod models;
 mod utils;
 
-pub use models::{FrpcProcesses, ProcessGuardState};
+pub use models::{
+    DownloadJobs, FrpcProcesses, GuardRules, MirrorHealthStates, ProcessGuardState, TunnelLogs,
+    TunnelStatuses,
+};
 
 use tauri::{
     menu::{MenuBuilder, MenuItemBuilder},
@@ -10,51 +13,28 @@ use tauri::{
     Emitter, Listener, Manager,
 };
 
-const AUTO_START_CONFIG_FILE: &str = "auto_start_tunnels.json";
-
 fn get_auto_start_tunnels_setting(app_handle: &tauri::AppHandle) -> bool {
-    let Some(app_data_dir) = app_handle.path().app_data_dir().ok() else {
-        return false;
-    };
-    let config_path = app_data_dir.join(AUTO_START_CONFIG_FILE);
-    if !config_path.exists() {
-        return false;
-    }
-    let Ok(content) = std::fs::read_to_string(&config_path) else {
-        return false;
-    };
-    serde_json::from_str::<serde_json::Value>(&content)
-        .ok()
-        .and_then(|config| config.get("enabled").and_then(|v| v.as_bool()))
+    commands::settings::load::<commands::settings::LauncherSettings>(app_handle)
+        .map(|settings| settings.auto_start_tunnels)
         .unwrap_or(false)
 }
 
 fn save_auto_start_tunnels_setting(app_handle: &tauri::AppHandle, enabled: bool) -> bool {
-    let Some(app_data_dir) = app_handle.path().app_data_dir().ok() else {
-        return false;
-    };
-    if std::fs::create_dir_all(&app_data_dir).is_err() {
-        return false;
-    }
-    let config_path = app_data_dir.join(AUTO_START_CONFIG_FILE);
-    let config = serde_json::json!({ "enabled": enabled });
-    std::fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap()).is_ok()
+    commands::settings::update(app_handle, |settings: &mut commands::settings::LauncherSettings| {
+        settings.auto_start_tunnels = enabled;
+        Ok(())
+    })
+    .is_ok()
 }
 
 fn cleanup_official_tunnel_configs(app_handle: &tauri::AppHandle) {
     let Ok(app_data_dir) = app_handle.path().app_data_dir() else {
         return;
     };
-    let Ok(entries) = std::fs::read_dir(&app_data_dir) else {
-        return;
-    };
-    for entry in entries.flatten() {
-        if let Ok(file_name) = entry.file_name().into_string() {
-            if file_name.starts_with("g_") && file_name.ends_with(".ini") {
-                let _ = std::fs::remove_file(entry.path());
-            }
-        }
-    }
+    // 接管的 frpc 仍在使用其配置文件
+    let processes = app_handle.state::<FrpcProcesses>();
+    let adopted = commands::process_adopt::adopted_config_paths(&processes);
+    commands::frpc_config::cleanup_generated_configs(&app_data_dir, &adopted);
 }
 
 fn build_tray_menu(app: &tauri::App) -> Result<tauri::menu::Menu<tauri::Wry>, Box<dyn std::error::Error>> {
@@ -123,7 +103,7 @@ pub fn run() {
                         }
                     }
                     "quit" => {
-                        app.exit(0);
+                        commands::shutdown::request_shutdown(app, 0);
                     }
                     _ => {}
                 })
@@ -205,28 +185,68 @@ pub fn run() {
             });
 
             let app_handle = app.handle().clone();
-            commands::process_guard::start_guard_monitor(app_handle.clone());
+            commands::guard_rules::load_guard_rules(&app_handle);
+            commands::process_adopt::reconcile_orphans(&app_handle);
+            commands::process_guard::restore_guard_state(&app_handle);
+            commands::custom_tunnel::seal_plaintext_configs(&app_handle);
 
             cleanup_official_tunnel_configs(&app_handle);
+            commands::tunnel_log::cleanup_expired_logs(&app_handle);
+            commands::process_guard::resume_guarded_tunnels(&app_handle);
+            commands::autostart::auto_start_tunnels_on_launch(app_handle.clone());
+
+            #[cfg(unix)]
+            commands::shutdown::listen_for_signals(app_handle.clone());
 
             Ok(())
         })
         .manage(FrpcProcesses::new())
         .manage(ProcessGuardState::new())
+        .manage(GuardRules::new())
+        .manage(TunnelStatuses::new())
+        .manage(TunnelLogs::new())
+        .manage(MirrorHealthStates::new())
+        .manage(DownloadJobs::new())
         .invoke_handler(tauri::generate_handler![
             commands::check_frpc_exists,
             commands::get_frpc_directory,
             commands::get_download_url,
             commands::download_frpc,
+            commands::check_frpc_update,
+            commands::upgrade_frpc,
+            commands::rollback_frpc,
+            commands::download_mirror::get_download_mirrors,
+            commands::download_mirror::set_download_mirrors,
+            commands::download_mirror::reset_download_mirrors,
+            commands::download_job::list_downloads,
+            commands::download_job::cancel_download,
+            commands::download_job::pause_download,
+            commands::download_job::resume_download,
+            commands::frpc_version::list_frpc_versions,
+            commands::frpc_version::get_tunnel_frpc_version,
+            commands::frpc_version::set_tunnel_frpc_version,
             commands::start_frpc,
             commands::stop_frpc,
             commands::is_frpc_running,
             commands::get_running_tunnels,
+            commands::batch::start_tunnels,
+            commands::batch::stop_tunnels,
+            commands::batch::restart_tunnels,
+            commands::tunnel_status::get_tunnel_status,
+            commands::tunnel_status::get_tunnel_exit_info,
+            commands::tunnel_log::list_tunnel_logs,
+            commands::tunnel_log::read_tunnel_log,
+            commands::tunnel_log::delete_tunnel_log,
             commands::is_autostart_enabled,
             commands::set_autostart,
             commands::get_auto_start_tunnels,
             commands::get_tunnel_auto_start,
             commands::set_tunnel_auto_start,
+            commands::set_tunnel_auto_start_delay,
+            commands::set_auto_start_order,
+            commands::autostart::get_launch_auto_start_state,
+            commands::profile::export_profile,
+            commands::profile::import_profile,
             commands::http_request,
             commands::hide_window,
             commands::show_window,
@@ -243,8 +263,16 @@ pub fn run() {
             commands::copy_background_video,
             commands::copy_background_image,
             commands::get_background_video_path,
+            commands::process_stop::get_stop_grace_period,
+            commands::process_stop::set_stop_grace_period,
             commands::process_guard::set_process_guard_enabled,
             commands::process_guard::get_process_guard_enabled,
+            commands::process_guard::get_process_guard_config,
+            commands::process_guard::set_process_guard_config,
+            commands::process_guard::get_guard_restart_stats,
+            commands::guard_rules::list_guard_rules,
+            commands::guard_rules::add_guard_rule,
+            commands::guard_rules::remove_guard_rule,
             commands::process_guard::add_guarded_process,
             commands::process_guard::add_guarded_custom_tunnel,
             commands::process_guard::remove_guarded_process,
@@ -255,6 +283,13 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app_handle, event| match event {
+            tauri::RunEvent::ExitRequested { code, api, .. } => {
+                // 先停止所有隧道，完成后由退出流程再次触发退出
+                if !commands::shutdown::is_shutdown_finished() {
+                    api.prevent_exit();
+                    commands::shutdown::request_shutdown(app_handle, code.unwrap_or(0));
+                }
+            }
             #[cfg(target_os = "macos")]
             tauri::RunEvent::Reopen { .. } => {
                 if let Some(window) = app_handle.get_webview_window("main") {
@@ -1,8 +1,12 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::process::Child;
-use std::sync::atomic::AtomicBool;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::watch;
 
 // 下载进度结构
 #[derive(Serialize, Clone)]
@@ -10,6 +14,69 @@ pub struct DownloadProgress {
     pub downloaded: u64,
     pub total: u64,
     pub percentage: f64,
+    // 当前使用的下载镜像名称
+    pub mirror: String,
+    pub job_id: u64,
+}
+
+// 下载任务的控制信号
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownloadControl {
+    Run,
+    Pause,
+    Cancel,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+// 下载任务状态变化事件，completed / cancelled / failed 为最终状态
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadStatusEvent {
+    pub job_id: u64,
+    pub status: DownloadStatus,
+    pub error: Option<String>,
+}
+
+// 进行中的下载任务
+pub struct DownloadJob {
+    pub frpc_path: PathBuf,
+    pub control: tokio::sync::watch::Sender<DownloadControl>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadJobInfo {
+    pub job_id: u64,
+    pub frpc_path: String,
+    pub status: DownloadStatus,
+}
+
+// 存储进行中的下载任务，按任务 id 索引
+pub struct DownloadJobs {
+    pub jobs: Mutex<HashMap<u64, DownloadJob>>,
+    pub next_id: AtomicU64,
+}
+
+impl Default for DownloadJobs {
+    fn default() -> Self {
+        Self {
+            jobs: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+impl DownloadJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
 }
 
 // API 响应数据结构
@@ -24,10 +91,19 @@ pub struct FrpcInfoResponse {
 #[derive(Deserialize, Debug)]
 pub struct FrpcInfoData {
     pub downloads: Vec<FrpcDownload>,
-    #[allow(dead_code)]
     pub version: String,
-    #[allow(dead_code)]
     pub release_notes: Vec<String>,
+    // 清单中可选的历史版本列表，用于下载指定版本
+    #[serde(default)]
+    pub versions: Vec<FrpcRelease>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FrpcRelease {
+    pub version: String,
+    #[serde(default)]
+    pub release_notes: Vec<String>,
+    pub downloads: Vec<FrpcDownload>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -40,6 +116,9 @@ pub struct FrpcDownload {
     pub link: String,
     pub arch: String,
     pub size: u64,
+    // 链接为压缩包时，解压出的 frpc 的 sha256
+    #[serde(default)]
+    pub binary_hash: Option<String>,
 }
 
 // 下载信息结构
@@ -47,56 +126,608 @@ pub struct DownloadInfo {
     pub url: String,
     pub hash: String,
     pub size: u64,
+    pub binary_hash: Option<String>,
+    pub version: String,
+    pub release_notes: Vec<String>,
+}
+
+// frpc 更新检查结果
+#[derive(Serialize, Clone, Debug)]
+pub struct FrpcUpdateInfo {
+    // 未安装或无法识别时为空
+    pub installed_version: Option<String>,
+    pub latest_version: String,
+    pub update_available: bool,
+    pub release_notes: Vec<String>,
+}
+
+// 已安装的 frpc 版本
+#[derive(Serialize, Clone, Debug)]
+pub struct InstalledFrpcVersion {
+    pub version: String,
+    pub path: String,
+    // 固定使用该版本的隧道
+    pub tunnels: Vec<TunnelKey>,
+}
+
+// frpc_versions.json 的存储结构，记录各隧道固定使用的 frpc 版本
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct FrpcVersionPins {
+    #[serde(default)]
+    pub pins: BTreeMap<TunnelKey, String>,
+}
+
+// 下载镜像，manifest_url 为 frpc_info.json 的地址
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DownloadMirror {
+    pub id: String,
+    pub name: String,
+    pub manifest_url: String,
+    // 设置后只从清单中取文件名，从该地址下载
+    #[serde(default)]
+    pub binary_base_url: Option<String>,
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+// download_mirrors.json 的存储结构，按顺序尝试各镜像
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DownloadMirrors {
+    pub mirrors: Vec<DownloadMirror>,
+}
+
+// 镜像的健康状态，仅在本次运行期间记录
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct MirrorHealth {
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<String>,
+    pub last_failure_at: Option<String>,
+    pub last_latency_ms: Option<u64>,
+    #[serde(skip)]
+    pub failed_at: Option<Instant>,
+}
+
+// 存储各镜像的健康状态，按镜像 id 索引
+#[derive(Default)]
+pub struct MirrorHealthStates {
+    pub health: Mutex<HashMap<String, MirrorHealth>>,
+}
+
+impl MirrorHealthStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DownloadMirrorStatus {
+    #[serde(flatten)]
+    pub mirror: DownloadMirror,
+    pub health: MirrorHealth,
+    // 连续失败的镜像在冷却期内排到最后尝试
+    pub cooling_down: bool,
 }
 
 // 存储运行中的frpc进程
+#[derive(Default)]
 pub struct FrpcProcesses {
-    pub processes: Mutex<HashMap<i32, Child>>,
+    pub processes: Mutex<HashMap<TunnelKey, FrpcChild>>,
+    // 启动器重启后接管的 frpc 进程
+    pub adopted: Mutex<HashMap<TunnelKey, AdoptedProcess>>,
+    // 正在启动、尚未记录进程的隧道，防止并发启动同一隧道
+    pub starting: Mutex<HashSet<TunnelKey>>,
 }
 
 impl FrpcProcesses {
     pub fn new() -> Self {
-        Self {
-            processes: Mutex::new(HashMap::new()),
+        Self::default()
+    }
+}
+
+// frpc 子进程的运行状态，由退出监听线程在进程退出后更新
+#[derive(Clone, Copy, Debug)]
+pub enum ChildState {
+    Running,
+    // wait 失败时没有退出状态
+    Exited(Option<ExitStatus>),
+}
+
+// 运行中的 frpc 子进程，Child 由退出监听线程持有并阻塞等待，这里只保留 PID
+pub struct FrpcChild {
+    pub pid: u32,
+    pub state: watch::Receiver<ChildState>,
+}
+
+// 接管的 frpc 进程
+#[derive(Clone, Debug)]
+pub struct AdoptedProcess {
+    pub pid: u32,
+    pub start_time: u64,
+    pub config_path: String,
+    // 通知日志读取线程停止读取输出文件
+    pub output_stop: Arc<AtomicBool>,
+}
+
+// 隧道 PID 文件内容
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TunnelPidRecord {
+    pub tunnel_key: TunnelKey,
+    pub pid: u32,
+    pub start_time: u64,
+    pub config_path: String,
+    pub binary_hash: String,
+}
+
+// 停止 frpc 进程的方式
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StopMethod {
+    Graceful,
+    Forced,
+}
+
+// 隧道来源：ChmlFrp API 隧道或自定义隧道
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TunnelKind {
+    Api,
+    Custom,
+}
+
+// 隧道的唯一标识，序列化为 "api_<id>" 或 "custom_<id>"，与前端使用的隧道键一致
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TunnelKey {
+    Api(i32),
+    Custom(String),
+}
+
+impl TunnelKey {
+    pub fn new(kind: TunnelKind, tunnel_id: &str) -> Result<Self, String> {
+        match kind {
+            TunnelKind::Api => tunnel_id
+                .parse()
+                .map(TunnelKey::Api)
+                .map_err(|_| format!("无效的隧道 ID: {}", tunnel_id)),
+            TunnelKind::Custom if tunnel_id.is_empty() => Err("自定义隧道 ID 不能为空".to_string()),
+            TunnelKind::Custom => Ok(TunnelKey::Custom(tunnel_id.to_string())),
+        }
+    }
+
+    pub fn kind(&self) -> TunnelKind {
+        match self {
+            TunnelKey::Api(_) => TunnelKind::Api,
+            TunnelKey::Custom(_) => TunnelKind::Custom,
+        }
+    }
+}
+
+impl fmt::Display for TunnelKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TunnelKey::Api(id) => write!(f, "api_{}", id),
+            TunnelKey::Custom(id) => write!(f, "custom_{}", id),
         }
     }
 }
 
+impl std::str::FromStr for TunnelKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('_') {
+            Some(("api", id)) => TunnelKey::new(TunnelKind::Api, id),
+            Some(("custom", id)) => TunnelKey::new(TunnelKind::Custom, id),
+            _ => Err(format!("无效的隧道标识: {}", s)),
+        }
+    }
+}
+
+impl Serialize for TunnelKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TunnelKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// 批量操作的目标隧道，API 隧道可附带启动配置
+#[derive(Deserialize, Clone, Debug)]
+pub struct BatchTunnelTarget {
+    pub kind: TunnelKind,
+    pub tunnel_id: String,
+    #[serde(default)]
+    pub config: Option<TunnelConfig>,
+}
+
+// 批量操作中单个隧道的执行结果
+#[derive(Serialize, Clone, Debug)]
+pub struct BatchTunnelResult {
+    pub kind: TunnelKind,
+    pub tunnel_id: String,
+    pub success: bool,
+    pub message: String,
+}
+
+// 启动软件时后端自动启动隧道的状态，results 在全部启动完成前为空
+#[derive(Serialize, Clone, Debug)]
+pub struct LaunchAutoStartState {
+    pub enabled: bool,
+    pub results: Option<Vec<BatchTunnelResult>>,
+}
+
+// 单个自动启动隧道的设置
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutoStartEntry {
+    pub kind: TunnelKind,
+    pub tunnel_id: String,
+    // 启动该隧道前等待的毫秒数
+    #[serde(default)]
+    pub delay_ms: u64,
+    // 启动顺序，数值越小越先启动
+    #[serde(default)]
+    pub order: u32,
+}
+
+// tunnel_auto_start.json 的存储结构
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AutoStartStore {
+    #[serde(default)]
+    pub entries: Vec<AutoStartEntry>,
+}
+
+// 配置备份中单个文件的记录
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileManifestEntry {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+// 配置备份清单
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub files: Vec<ProfileManifestEntry>,
+}
+
+// 导入备份时遇到已存在项目的处理方式
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileConflictStrategy {
+    #[default]
+    Skip,
+    Overwrite,
+}
+
+#[derive(Serialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileItemCategory {
+    Settings,
+    CustomTunnel,
+    AutoStart,
+    GuardRules,
+    Background,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileImportAction {
+    Create,
+    Overwrite,
+    Skip,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileImportItem {
+    pub category: ProfileItemCategory,
+    pub name: String,
+    pub action: ProfileImportAction,
+}
+
+// 导入结果，dry_run 时仅为预览，不修改任何文件
+#[derive(Serialize, Clone, Debug)]
+pub struct ProfileImportReport {
+    pub manifest: ProfileManifest,
+    pub dry_run: bool,
+    pub items: Vec<ProfileImportItem>,
+}
+
 // 隧道类型
-#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum TunnelType {
     Api { config: TunnelConfig },
     Custom { original_id: String },
 }
 
 // 进程守护信息
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ProcessGuardInfo {
-    pub tunnel_id: i32,
     pub tunnel_type: TunnelType,
 }
 
+impl ProcessGuardInfo {
+    pub fn key(&self) -> TunnelKey {
+        match &self.tunnel_type {
+            TunnelType::Api { config } => TunnelKey::Api(config.tunnel_id),
+            TunnelType::Custom { original_id } => TunnelKey::Custom(original_id.clone()),
+        }
+    }
+}
+
+// 守护进程重启策略
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ProcessGuardConfig {
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+    pub jitter_ratio: f64,
+    pub max_restarts: u32,
+    pub restart_window_secs: u64,
+}
+
+impl Default for ProcessGuardConfig {
+    fn default() -> Self {
+        Self {
+            base_delay_secs: 1,
+            max_delay_secs: 60,
+            jitter_ratio: 0.2,
+            max_restarts: 5,
+            restart_window_secs: 600,
+        }
+    }
+}
+
+// 单个隧道的重启记录
+#[derive(Default)]
+pub struct RestartStats {
+    pub restart_times: VecDeque<Instant>,
+    pub total_restarts: u32,
+    pub pending: bool,
+    pub gave_up: bool,
+    pub last_restart_at: Option<String>,
+}
+
+// 提供给前端的重启计数
+#[derive(Serialize, Clone)]
+pub struct GuardRestartInfo {
+    pub tunnel_key: TunnelKey,
+    pub total_restarts: u32,
+    pub restarts_in_window: u32,
+    pub pending: bool,
+    pub gave_up: bool,
+    pub last_restart_at: Option<String>,
+}
+
+// 守护进程放弃重启事件
+#[derive(Serialize, Clone)]
+pub struct GuardGaveUpMessage {
+    pub tunnel_key: TunnelKey,
+    pub restarts: u32,
+    pub window_secs: u64,
+    pub timestamp: String,
+}
+
+// 守护规则命中后的处理方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GuardRuleAction {
+    StopGuard,
+    StopTunnel,
+    NotifyOnly,
+}
+
+// 守护日志匹配规则
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuardRule {
+    pub id: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    pub action: GuardRuleAction,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+// 已编译的守护规则
+pub struct CompiledGuardRule {
+    pub rule: GuardRule,
+    pub matcher: regex::Regex,
+}
+
+// 守护规则状态管理
+#[derive(Default)]
+pub struct GuardRules {
+    pub rules: Mutex<Vec<CompiledGuardRule>>,
+}
+
+impl GuardRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// 守护规则命中消息
+#[derive(Serialize, Clone)]
+pub struct GuardRuleMatchedMessage {
+    pub tunnel_key: TunnelKey,
+    pub rule_id: String,
+    pub pattern: String,
+    pub action: GuardRuleAction,
+    pub message: String,
+    pub timestamp: String,
+}
+
+// 持久化到磁盘的守护进程状态
+#[derive(Serialize, Deserialize, Default)]
+pub struct PersistedGuardState {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub config: ProcessGuardConfig,
+    #[serde(default)]
+    pub guarded: Vec<ProcessGuardInfo>,
+}
+
 // 守护进程状态管理
+#[derive(Default)]
 pub struct ProcessGuardState {
     pub enabled: Arc<AtomicBool>,
-    pub guarded_processes: Arc<Mutex<HashMap<i32, ProcessGuardInfo>>>,
-    pub manually_stopped: Arc<Mutex<std::collections::HashSet<i32>>>,
+    pub guarded_processes: Arc<Mutex<HashMap<TunnelKey, ProcessGuardInfo>>>,
+    pub manually_stopped: Arc<Mutex<std::collections::HashSet<TunnelKey>>>,
+    pub config: Arc<Mutex<ProcessGuardConfig>>,
+    pub restart_stats: Arc<Mutex<HashMap<TunnelKey, RestartStats>>>,
+    pub state_file: Mutex<Option<PathBuf>>,
 }
 
 impl ProcessGuardState {
     pub fn new() -> Self {
-        Self {
-            enabled: Arc::new(AtomicBool::new(false)),
-            guarded_processes: Arc::new(Mutex::new(HashMap::new())),
-            manually_stopped: Arc::new(Mutex::new(std::collections::HashSet::new())),
-        }
+        Self::default()
+    }
+}
+
+// 隧道连接状态
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TunnelStatus {
+    Starting,
+    LoggingIn,
+    Online,
+    Reconnecting,
+    Failed { reason: String },
+    Stopped,
+}
+
+// 各隧道的连接状态
+#[derive(Default)]
+pub struct TunnelStatuses {
+    pub statuses: Mutex<HashMap<TunnelKey, TunnelStatus>>,
+    pub exits: Mutex<HashMap<TunnelKey, FrpcExitInfo>>,
+}
+
+impl TunnelStatuses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// frpc 进程退出信息
+#[derive(Serialize, Clone, Debug)]
+pub struct FrpcExitInfo {
+    pub tunnel_key: TunnelKey,
+    pub pid: u32,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub success: bool,
+    pub stop_method: Option<StopMethod>,
+    pub timestamp: String,
+}
+
+// 隧道状态变化事件
+#[derive(Serialize, Clone)]
+pub struct TunnelStatusMessage {
+    pub tunnel_key: TunnelKey,
+    pub status: TunnelStatus,
+    pub timestamp: String,
+}
+
+// 运行中的隧道及其状态
+#[derive(Serialize, Clone)]
+pub struct RunningTunnel {
+    pub tunnel_key: TunnelKey,
+    pub status: TunnelStatus,
+}
+
+// 隧道日志文件写入句柄
+pub struct TunnelLogWriter {
+    pub file: std::fs::File,
+    pub size: u64,
+    pub created_at: chrono::DateTime<chrono::Local>,
+}
+
+// 各隧道正在写入的日志文件
+#[derive(Default)]
+pub struct TunnelLogs {
+    pub writers: Mutex<HashMap<TunnelKey, TunnelLogWriter>>,
+}
+
+impl TunnelLogs {
+    pub fn new() -> Self {
+        Self::default()
     }
 }
 
+// 日志文件信息
+#[derive(Serialize, Clone)]
+pub struct TunnelLogFile {
+    pub file_name: String,
+    pub tunnel_key: TunnelKey,
+    pub size: u64,
+    pub modified: String,
+}
+
+// 分页读取的日志内容
+#[derive(Serialize, Clone)]
+pub struct TunnelLogPage {
+    pub file_name: String,
+    pub lines: Vec<String>,
+    pub offset: usize,
+    pub total_lines: usize,
+    pub has_more: bool,
+}
+
 // 日志消息结构
 #[derive(Serialize, Clone)]
 pub struct LogMessage {
-    pub tunnel_id: i32,
+    pub tunnel_key: TunnelKey,
+    pub message: String,
+    pub timestamp: String,
+}
+
+// frpc 日志级别
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FrpcLogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+// frpc 日志中识别出的事件类型
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FrpcLogEventKind {
+    LoginSuccess,
+    LoginFailed,
+    ConnectFailed,
+    ProxyStarted,
+    ProxyStartFailed,
+    Reconnecting,
+}
+
+// 结构化日志事件
+#[derive(Serialize, Clone, Debug)]
+pub struct FrpcLogEvent {
+    pub tunnel_key: TunnelKey,
+    pub kind: FrpcLogEventKind,
+    pub level: Option<FrpcLogLevel>,
+    pub source: Option<String>,
+    pub run_id: Option<String>,
+    pub proxy_name: Option<String>,
+    pub reason: Option<String>,
     pub message: String,
     pub timestamp: String,
 }
@@ -112,7 +743,7 @@ pub struct HttpRequestOptions {
 }
 
 // 隧道配置信息
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TunnelConfig {
     pub tunnel_id: i32,
     pub tunnel_name: String,
@@ -129,4 +760,73 @@ pub struct TunnelConfig {
     pub log_level: String,
     pub force_tls: bool,
     pub kcp_optimization: bool,
+    #[serde(default)]
+    pub config_format: Option<String>,
+}
+
+impl TunnelConfig {
+    // 写入磁盘前去掉 token，避免明文保存
+    pub fn without_tokens(&self) -> Self {
+        Self {
+            user_token: String::new(),
+            node_token: String::new(),
+            ..self.clone()
+        }
+    }
+
+    pub fn has_tokens(&self) -> bool {
+        !self.user_token.is_empty() && !self.node_token.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tunnel_key_round_trip() {
+        let cases = [
+            ("api_123", TunnelKey::Api(123)),
+            ("api_-1", TunnelKey::Api(-1)),
+            ("custom_web", TunnelKey::Custom("web".to_string())),
+            (
+                "custom_my_tunnel_id",
+                TunnelKey::Custom("my_tunnel_id".to_string()),
+            ),
+            ("custom_api_1", TunnelKey::Custom("api_1".to_string())),
+        ];
+        for (text, key) in cases {
+            assert_eq!(text.parse::<TunnelKey>(), Ok(key.clone()), "{:?}", text);
+            assert_eq!(key.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn tunnel_key_malformed() {
+        let cases = [
+            "",
+            "api",
+            "api_",
+            "api_abc",
+            "api_1_2",
+            "api_99999999999",
+            "custom",
+            "custom_",
+            "foo_1",
+            "API_1",
+            "_1",
+        ];
+        for text in cases {
+            assert!(text.parse::<TunnelKey>().is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn tunnel_key_serde() {
+        let key = TunnelKey::Custom("my_tunnel".to_string());
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, "\"custom_my_tunnel\"");
+        assert_eq!(serde_json::from_str::<TunnelKey>(&json).unwrap(), key);
+        assert!(serde_json::from_str::<TunnelKey>("\"custom_\"").is_err());
+    }
 }
@@ -105,6 +105,7 @@ export function useDeepLink(
                   progress.percentage,
                   progress.downloaded,
                   progress.total,
+                  progress.mirror,
                 ),
                 {
                   duration: Infinity,
@@ -148,7 +149,7 @@ export function useDeepLink(
 
           const successLog = logs.find(
             (log) =>
-              log.tunnel_id === data.tunnelId &&
+              log.tunnel_key === `api_${data.tunnelId}` &&
               log.message.includes("映射启动成功"),
           );
 
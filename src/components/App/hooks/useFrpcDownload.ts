@@ -49,6 +49,7 @@ export function useFrpcDownload(): {
                 progress.percentage,
                 progress.downloaded,
                 progress.total,
+                progress.mirror,
               ),
               {
                 id: downloadToastRef.current,
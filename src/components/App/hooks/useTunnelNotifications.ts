@@ -7,9 +7,9 @@ import { playTunnelSound } from "@/lib/sound";
 
 export function useTunnelNotifications(activeTab: string) {
   const processedLogsCountRef = useRef(0);
-  const notifiedSuccessRef = useRef<Set<number>>(new Set());
-  const notifiedErrorRef = useRef<Set<number>>(new Set());
-  const successLogAddedRef = useRef<Set<number>>(new Set());
+  const notifiedSuccessRef = useRef<Set<string>>(new Set());
+  const notifiedErrorRef = useRef<Set<string>>(new Set());
+  const successLogAddedRef = useRef<Set<string>>(new Set());
   const tunnelCacheRef = useRef<{
     updatedAt: number;
     data: Map<number, Tunnel>;
@@ -51,28 +51,33 @@ export function useTunnelNotifications(activeTab: string) {
       const skipNotifications = activeTab === "tunnels";
 
       for (const log of newLogs) {
-        const tunnelId = log.tunnel_id;
+        const tunnelKey = log.tunnel_key;
         const message = log.message;
         const hasLauncherSuccessLog = logs.some(
           (item) =>
-            item.tunnel_id === tunnelId &&
+            item.tunnel_key === tunnelKey &&
             item.message.includes("[ChmlFrpLauncher]") &&
             item.message.includes("启动成功"),
         );
 
         if (message.includes("frpc 进程已启动")) {
-          notifiedSuccessRef.current.delete(tunnelId);
-          notifiedErrorRef.current.delete(tunnelId);
-          successLogAddedRef.current.delete(tunnelId);
+          notifiedSuccessRef.current.delete(tunnelKey);
+          notifiedErrorRef.current.delete(tunnelKey);
+          successLogAddedRef.current.delete(tunnelKey);
         }
 
         if (message.includes("映射启动成功")) {
           if (
             !hasLauncherSuccessLog &&
-            !successLogAddedRef.current.has(tunnelId)
+            !successLogAddedRef.current.has(tunnelKey)
           ) {
-            successLogAddedRef.current.add(tunnelId);
+            successLogAddedRef.current.add(tunnelKey);
             void (async () => {
+              // 访问链接只对 API 隧道有意义
+              if (!tunnelKey.startsWith("api_")) {
+                return;
+              }
+              const tunnelId = Number(tunnelKey.slice("api_".length));
               const tunnel = await getTunnelById(tunnelId);
               if (!tunnel) {
                 return;
@@ -96,13 +101,13 @@ export function useTunnelNotifications(activeTab: string) {
                 .replace(/\//g, "/");
               if (link) {
                 logStore.addLog({
-                  tunnel_id: tunnelId,
+                  tunnel_key: tunnelKey,
                   message: `[I] [ChmlFrpLauncher] 隧道"${tunnelName}"启动成功，您可以通过"${link}"访问。`,
                   timestamp,
                 });
               } else {
                 logStore.addLog({
-                  tunnel_id: tunnelId,
+                  tunnel_key: tunnelKey,
                   message: `[I] [ChmlFrpLauncher] 隧道"${tunnelName}"启动成功。`,
                   timestamp,
                 });
@@ -116,16 +121,16 @@ export function useTunnelNotifications(activeTab: string) {
         }
 
         if (message.includes("映射启动成功")) {
-          if (!notifiedSuccessRef.current.has(tunnelId)) {
-            notifiedSuccessRef.current.add(tunnelId);
+          if (!notifiedSuccessRef.current.has(tunnelKey)) {
+            notifiedSuccessRef.current.add(tunnelKey);
             const soundEnabled =
               localStorage.getItem("tunnelSoundEnabled") !== "false";
             playTunnelSound("success", soundEnabled);
             toast.success("隧道启动成功", { duration: 4000 });
           }
         } else if (message.includes("启动失败")) {
-          if (!notifiedErrorRef.current.has(tunnelId)) {
-            notifiedErrorRef.current.add(tunnelId);
+          if (!notifiedErrorRef.current.has(tunnelKey)) {
+            notifiedErrorRef.current.add(tunnelKey);
             const soundEnabled =
               localStorage.getItem("tunnelSoundEnabled") !== "false";
             playTunnelSound("error", soundEnabled);
@@ -5,6 +5,7 @@ export function createDownloadProgressToast(
   percentage: number,
   downloaded: number,
   total: number,
+  mirror?: string,
 ) {
   return React.createElement(
     "div",
@@ -20,6 +21,13 @@ export function createDownloadProgressToast(
       { className: "text-xs text-muted-foreground" },
       `${percentage.toFixed(1)}% (${(downloaded / 1024 / 1024).toFixed(2)} MB / ${(total / 1024 / 1024).toFixed(2)} MB)`,
     ),
+    mirror
+      ? React.createElement(
+          "div",
+          { className: "text-xs text-muted-foreground" },
+          `下载源: ${mirror}`,
+        )
+      : null,
   );
 }
 
@@ -52,13 +52,26 @@ function getLogColorClass(level: LogLevel): string {
 export function Logs() {
   const [tunnels, setTunnels] = useState<Tunnel[]>([]);
   const [customTunnels, setCustomTunnels] = useState<CustomTunnel[]>([]);
-  const [selectedTunnelId, setSelectedTunnelId] = useState<number | null>(null);
+  const [selectedTunnelKey, setSelectedTunnelKey] = useState<string | null>(
+    null,
+  );
   const [logs, setLogs] = useState<LogMessage[]>([]);
   const [autoScroll, setAutoScroll] = useState(true);
   const scrollRef = useRef<HTMLDivElement>(null);
   const logsEndRef = useRef<HTMLDivElement>(null);
   const hasAutoSelectedRef = useRef(false);
 
+  const findApiTunnel = (tunnelKey: string) =>
+    tunnels.find((t) => `api_${t.id}` === tunnelKey);
+
+  const getTunnelName = (tunnelKey: string) => {
+    const tunnel = findApiTunnel(tunnelKey);
+    const customTunnel = customTunnels.find(
+      (t) => `custom_${t.id}` === tunnelKey,
+    );
+    return tunnel?.name || customTunnel?.name || tunnelKey;
+  };
+
   useEffect(() => {
     const loadTunnels = async () => {
       try {
@@ -70,7 +83,7 @@ export function Logs() {
 
         const runningTunnels = await frpcManager.getRunningTunnels();
         if (runningTunnels.length > 0) {
-          setSelectedTunnelId(runningTunnels[0]);
+          setSelectedTunnelKey(runningTunnels[0]);
         }
       } catch (err) {
         console.error("Failed to load tunnels:", err);
@@ -85,8 +98,8 @@ export function Logs() {
       setLogs(allLogs);
 
       if (!hasAutoSelectedRef.current && allLogs.length > 0) {
-        const firstTunnelId = allLogs[allLogs.length - 1].tunnel_id;
-        setSelectedTunnelId(firstTunnelId);
+        const firstTunnelKey = allLogs[allLogs.length - 1].tunnel_key;
+        setSelectedTunnelKey(firstTunnelKey);
         hasAutoSelectedRef.current = true;
       }
     });
@@ -110,8 +123,8 @@ export function Logs() {
 
   const handleSaveLogs = async () => {
     // 在函数内部重新计算 filteredLogs，确保使用最新的值
-    const logsToSave = selectedTunnelId
-      ? logs.filter((log) => log.tunnel_id === selectedTunnelId)
+    const logsToSave = selectedTunnelKey
+      ? logs.filter((log) => log.tunnel_key === selectedTunnelKey)
       : logs;
 
     if (logsToSave.length === 0) {
@@ -121,7 +134,7 @@ export function Logs() {
 
     try {
       const filePath = await save({
-        defaultPath: `frpc-logs-${selectedTunnelId || "all"}-${new Date().toISOString().slice(0, 10)}.txt`,
+        defaultPath: `frpc-logs-${selectedTunnelKey || "all"}-${new Date().toISOString().slice(0, 10)}.txt`,
         filters: [
           {
             name: "Text",
@@ -171,8 +184,8 @@ export function Logs() {
         }
 
         // 获取隧道信息
-        const selectedTunnel = selectedTunnelId
-          ? tunnels.find((t) => t.id === selectedTunnelId)
+        const selectedTunnel = selectedTunnelKey
+          ? findApiTunnel(selectedTunnelKey)
           : null;
 
         // 计算日志时间范围
@@ -181,11 +194,11 @@ export function Logs() {
         const lastLogTime = logTimes[logTimes.length - 1] || "未知";
 
         // 统计各隧道的日志数量
-        const tunnelLogCounts = new Map<number, number>();
+        const tunnelLogCounts = new Map<string, number>();
         logsToSave.forEach((log) => {
           tunnelLogCounts.set(
-            log.tunnel_id,
-            (tunnelLogCounts.get(log.tunnel_id) || 0) + 1,
+            log.tunnel_key,
+            (tunnelLogCounts.get(log.tunnel_key) || 0) + 1,
           );
         });
 
@@ -199,11 +212,11 @@ export function Logs() {
           selectedTunnel && selectedTunnel.localip && selectedTunnel.nport
             ? `本地: ${selectedTunnel.localip}:${selectedTunnel.nport} | 链接: ${selectedTunnel.ip && selectedTunnel.dorp ? `${selectedTunnel.ip}:${selectedTunnel.dorp}` : "未知"}`
             : null,
-          ...(!selectedTunnelId && tunnelLogCounts.size > 0
+          ...(!selectedTunnelKey && tunnelLogCounts.size > 0
             ? Array.from(tunnelLogCounts.keys())
-                .sort((a, b) => a - b)
-                .map((id) => {
-                  const tunnel = tunnels.find((t) => t.id === id);
+                .sort((a, b) => a.localeCompare(b))
+                .map((key) => {
+                  const tunnel = findApiTunnel(key);
                   if (!tunnel) return null;
                   const localAddr =
                     tunnel.localip && tunnel.nport
@@ -213,7 +226,7 @@ export function Logs() {
                     tunnel.ip && tunnel.dorp
                       ? `${tunnel.ip}:${tunnel.dorp}`
                       : "未知";
-                  return `  隧道${id}(${tunnel.name}): ${localAddr} → ${remoteAddr}`;
+                  return `  隧道${tunnel.id}(${tunnel.name}): ${localAddr} → ${remoteAddr}`;
                 })
                 .filter((line): line is string => line !== null)
             : []),
@@ -230,7 +243,7 @@ export function Logs() {
         const logContent = logsToSave
           .map(
             (log) =>
-              `[${log.timestamp}] [隧道 ${log.tunnel_id}] ${log.message}`,
+              `[${log.timestamp}] [隧道 ${log.tunnel_key}] ${log.message}`,
           )
           .join("\n");
 
@@ -248,13 +261,14 @@ export function Logs() {
   };
 
   // 过滤日志
-  const filteredLogs = selectedTunnelId
-    ? logs.filter((log) => log.tunnel_id === selectedTunnelId)
+  const filteredLogs = selectedTunnelKey
+    ? logs.filter((log) => log.tunnel_key === selectedTunnelKey)
     : logs;
 
   const tunnelOptions = [
-    { value: 0, label: "所有隧道" },
-    ...tunnels.map((t) => ({ value: t.id, label: t.name })),
+    { value: "", label: "所有隧道" },
+    ...tunnels.map((t) => ({ value: `api_${t.id}`, label: t.name })),
+    ...customTunnels.map((t) => ({ value: `custom_${t.id}`, label: t.name })),
   ];
 
   return (
@@ -265,9 +279,9 @@ export function Logs() {
         <div className="flex items-center gap-2">
           <Select
             options={tunnelOptions}
-            value={selectedTunnelId || 0}
+            value={selectedTunnelKey || ""}
             onChange={(value) =>
-              setSelectedTunnelId(value === 0 ? null : Number(value))
+              setSelectedTunnelKey(value === "" ? null : String(value))
             }
             placeholder="选择隧道"
             className="w-48"
@@ -307,7 +321,7 @@ export function Logs() {
           >
             {filteredLogs.length === 0 ? (
               <div className="text-muted-foreground">
-                {selectedTunnelId
+                {selectedTunnelKey
                   ? "等待日志输出..."
                   : "请选择一个隧道或启动隧道以查看日志"}
               </div>
@@ -315,19 +329,14 @@ export function Logs() {
               filteredLogs.map((log, index) => {
                 const logLevel = getLogLevel(log.message);
                 const colorClass = getLogColorClass(logLevel);
-                const tunnel = tunnels.find((t) => t.id === log.tunnel_id);
-                const customTunnel = customTunnels.find(
-                  (t) => t.hashed_id === log.tunnel_id,
-                );
-                const tunnelName =
-                  tunnel?.name || customTunnel?.name || String(log.tunnel_id);
+                const tunnelName = getTunnelName(log.tunnel_key);
                 const showTimestamp = logLevel === "software";
                 return (
                   <div
                     key={index}
                     className="hover:bg-foreground/5 px-2 py-0.5 rounded"
                   >
-                    {!selectedTunnelId && (
+                    {!selectedTunnelKey && (
                       <span className="text-muted-foreground">
                         [隧道{tunnelName}]{" "}
                       </span>
@@ -12,7 +12,13 @@ interface UpdateSectionProps {
   currentVersion: string;
   onCheckUpdate: () => void;
   isDownloading: boolean;
+  isDownloadPaused: boolean;
+  canControlDownload: boolean;
   onRedownloadFrpc: () => void;
+  onCheckFrpcUpdate: () => void;
+  onRollbackFrpc: () => void;
+  onTogglePauseDownload: () => void;
+  onCancelDownload: () => void;
 }
 
 export function UpdateSection({
@@ -20,7 +26,13 @@ export function UpdateSection({
   currentVersion,
   onCheckUpdate,
   isDownloading,
+  isDownloadPaused,
+  canControlDownload,
   onRedownloadFrpc,
+  onCheckFrpcUpdate,
+  onRollbackFrpc,
+  onTogglePauseDownload,
+  onCancelDownload,
 }: UpdateSectionProps) {
   return (
     <div className="space-y-3">
@@ -61,10 +73,48 @@ export function UpdateSection({
           <ItemContent>
             <ItemTitle>frpc 客户端</ItemTitle>
             <ItemDescription className="text-xs">
-              重新下载 frpc 客户端程序
+              检查更新或重新下载 frpc 客户端程序
             </ItemDescription>
           </ItemContent>
           <ItemActions>
+            {canControlDownload && (
+              <>
+                <button
+                  onClick={onTogglePauseDownload}
+                  className="px-3 py-1.5 text-xs rounded transition-colors bg-card border border-border hover:bg-accent/50"
+                >
+                  {isDownloadPaused ? "继续" : "暂停"}
+                </button>
+                <button
+                  onClick={onCancelDownload}
+                  className="px-3 py-1.5 text-xs rounded transition-colors bg-card border border-border hover:bg-accent/50"
+                >
+                  取消
+                </button>
+              </>
+            )}
+            <button
+              onClick={onCheckFrpcUpdate}
+              disabled={isDownloading}
+              className={`px-3 py-1.5 text-xs rounded transition-colors ${
+                isDownloading
+                  ? "bg-muted text-muted-foreground cursor-not-allowed"
+                  : "bg-card border border-border hover:bg-accent/50"
+              }`}
+            >
+              检查更新
+            </button>
+            <button
+              onClick={onRollbackFrpc}
+              disabled={isDownloading}
+              className={`px-3 py-1.5 text-xs rounded transition-colors ${
+                isDownloading
+                  ? "bg-muted text-muted-foreground cursor-not-allowed"
+                  : "bg-card border border-border hover:bg-accent/50"
+              }`}
+            >
+              回滚
+            </button>
             <button
               onClick={onRedownloadFrpc}
               disabled={isDownloading}
@@ -74,7 +124,11 @@ export function UpdateSection({
                   : "bg-foreground text-background hover:opacity-90"
               }`}
             >
-              {isDownloading ? "下载中..." : "重新下载"}
+              {isDownloading
+                ? isDownloadPaused
+                  ? "已暂停"
+                  : "下载中..."
+                : "重新下载"}
             </button>
           </ItemActions>
         </Item>
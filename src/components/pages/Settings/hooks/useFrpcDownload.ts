@@ -1,37 +1,123 @@
-import { useState, useCallback } from "react";
+import { useState, useCallback, useEffect } from "react";
 import { toast } from "sonner";
-import { message } from "@tauri-apps/plugin-dialog";
-import { frpcDownloader } from "@/services/frpcDownloader";
+import { ask, message } from "@tauri-apps/plugin-dialog";
+import {
+  frpcDownloader,
+  DOWNLOAD_CANCELLED_ERROR,
+} from "@/services/frpcDownloader";
 
 export interface DownloadProgress {
   percentage: number;
   downloaded: number;
   total: number;
+  mirror: string;
+  job_id: number;
+}
+
+// 有隧道正在使用 frpc 时询问是否强制替换
+async function runWithForceConfirm<T>(
+  run: (force: boolean) => Promise<T>,
+): Promise<T> {
+  try {
+    return await run(false);
+  } catch (error) {
+    const errorMsg = error instanceof Error ? error.message : String(error);
+    if (!errorMsg.includes("强制替换")) {
+      throw error;
+    }
+    const confirmed = await ask(
+      `${errorMsg}\n\n强制替换后，运行中的隧道需要重新启动才会使用新的 frpc。是否继续？`,
+      { title: "frpc 正在使用中", kind: "warning" },
+    );
+    if (!confirmed) {
+      throw new Error("已取消替换");
+    }
+    return await run(true);
+  }
 }
 
 export function useFrpcDownload() {
   const [isDownloading, setIsDownloading] = useState(false);
   const [progress, setProgress] = useState<DownloadProgress | null>(null);
+  const [jobId, setJobId] = useState<number | null>(null);
+  const [isPaused, setIsPaused] = useState(false);
+
+  // 下载期间跟踪当前任务，用于暂停和取消
+  useEffect(() => {
+    if (!isDownloading) return;
+
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+    frpcDownloader
+      .onDownloadStatus((event) => {
+        if (event.status === "running" || event.status === "paused") {
+          setJobId(event.job_id);
+          setIsPaused(event.status === "paused");
+        } else {
+          setJobId(null);
+          setIsPaused(false);
+        }
+      })
+      .then(async (fn) => {
+        if (disposed) {
+          fn();
+          return;
+        }
+        unlisten = fn;
+
+        // 开始监听前任务可能已经登记
+        const jobs = await frpcDownloader.listDownloads();
+        const job = jobs[jobs.length - 1];
+        if (!disposed && job) {
+          setJobId(job.job_id);
+          setIsPaused(job.status === "paused");
+        }
+      })
+      .catch((error) => {
+        console.error("监听下载状态失败:", error);
+      });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+      setJobId(null);
+      setIsPaused(false);
+    };
+  }, [isDownloading]);
 
   const handleRedownloadFrpc = useCallback(async () => {
     if (isDownloading) return;
 
     setIsDownloading(true);
-    setProgress({ percentage: 0, downloaded: 0, total: 0 });
+    setProgress({
+      percentage: 0,
+      downloaded: 0,
+      total: 0,
+      mirror: "",
+      job_id: 0,
+    });
     const toastId = toast.loading("正在下载 frpc 客户端...", {
       duration: Infinity,
     });
 
     try {
-      await frpcDownloader.downloadFrpc((progressData) => {
-        setProgress(progressData);
-        const downloadedMB = (progressData.downloaded / 1024 / 1024).toFixed(2);
-        const totalMB = (progressData.total / 1024 / 1024).toFixed(2);
-        toast.loading(
-          `正在下载 frpc 客户端... ${progressData.percentage.toFixed(1)}% (${downloadedMB} MB / ${totalMB} MB)`,
-          { id: toastId, duration: Infinity },
-        );
-      });
+      await runWithForceConfirm((force) =>
+        frpcDownloader.downloadFrpc(
+          (progressData) => {
+            setProgress(progressData);
+            const downloadedMB = (progressData.downloaded / 1024 / 1024).toFixed(
+              2,
+            );
+            const totalMB = (progressData.total / 1024 / 1024).toFixed(2);
+            toast.loading(
+              `正在从 ${progressData.mirror} 下载 frpc 客户端... ${progressData.percentage.toFixed(1)}% (${downloadedMB} MB / ${totalMB} MB)`,
+              { id: toastId, duration: Infinity },
+            );
+          },
+          undefined,
+          force,
+        ),
+      );
 
       toast.success("frpc 客户端下载成功", {
         id: toastId,
@@ -39,6 +125,10 @@ export function useFrpcDownload() {
       });
     } catch (error) {
       const errorMsg = error instanceof Error ? error.message : String(error);
+      if (errorMsg.includes(DOWNLOAD_CANCELLED_ERROR)) {
+        toast.info("已取消下载", { id: toastId, duration: 3000 });
+        return;
+      }
 
       // 检测是否可能是 Windows 杀毒软件拦截
       const isWindows =
@@ -81,9 +171,143 @@ export function useFrpcDownload() {
     }
   }, [isDownloading]);
 
+  const handleCheckFrpcUpdate = useCallback(async () => {
+    if (isDownloading) return;
+
+    setIsDownloading(true);
+    const toastId = toast.loading("正在检查 frpc 更新...", {
+      duration: Infinity,
+    });
+
+    try {
+      const info = await frpcDownloader.checkFrpcUpdate();
+      if (!info.update_available) {
+        toast.success(
+          `frpc 已是最新版本${info.installed_version ? ` (v${info.installed_version})` : ""}`,
+          { id: toastId, duration: 3000 },
+        );
+        return;
+      }
+      toast.dismiss(toastId);
+
+      const notes =
+        info.release_notes.length > 0
+          ? `\n\n更新内容：\n${info.release_notes.map((note) => `- ${note}`).join("\n")}`
+          : "";
+      const confirmed = await ask(
+        `当前版本: ${info.installed_version ? `v${info.installed_version}` : "未知"}\n` +
+          `最新版本: v${info.latest_version}${notes}\n\n` +
+          "升级后旧版本会保留，可用于回滚。是否立即升级？",
+        { title: "frpc 有新版本", kind: "info" },
+      );
+      if (!confirmed) return;
+
+      const upgradeToastId = toast.loading("正在升级 frpc 客户端...", {
+        duration: Infinity,
+      });
+      setProgress({
+        percentage: 0,
+        downloaded: 0,
+        total: 0,
+        mirror: "",
+        job_id: 0,
+      });
+      try {
+        const version = await runWithForceConfirm((force) =>
+          frpcDownloader.upgradeFrpc((progressData) => {
+            setProgress(progressData);
+            toast.loading(
+              `正在从 ${progressData.mirror} 升级 frpc 客户端... ${progressData.percentage.toFixed(1)}%`,
+              { id: upgradeToastId, duration: Infinity },
+            );
+          }, force),
+        );
+        toast.success(`frpc 已升级到 v${version}，重新启动隧道后生效`, {
+          id: upgradeToastId,
+          duration: 5000,
+        });
+      } catch (error) {
+        const errorMsg =
+          error instanceof Error ? error.message : String(error);
+        if (errorMsg.includes(DOWNLOAD_CANCELLED_ERROR)) {
+          toast.info("已取消升级", { id: upgradeToastId, duration: 3000 });
+          return;
+        }
+        toast.error(`升级失败: ${errorMsg}`, {
+          id: upgradeToastId,
+          duration: 8000,
+        });
+      }
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      toast.error(`检查更新失败: ${errorMsg}`, {
+        id: toastId,
+        duration: 5000,
+      });
+    } finally {
+      setIsDownloading(false);
+      setProgress(null);
+    }
+  }, [isDownloading]);
+
+  const handleRollbackFrpc = useCallback(async () => {
+    if (isDownloading) return;
+
+    const confirmed = await ask(
+      "将恢复上一次替换前的 frpc 客户端，当前版本会保留为备份，再次回滚即可撤销。是否继续？",
+      { title: "回滚 frpc", kind: "warning" },
+    );
+    if (!confirmed) return;
+
+    setIsDownloading(true);
+    try {
+      await runWithForceConfirm((force) =>
+        frpcDownloader.rollbackFrpc(undefined, force),
+      );
+      toast.success("frpc 已回滚，重新启动隧道后生效", { duration: 5000 });
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      toast.error(`回滚失败: ${errorMsg}`, { duration: 5000 });
+    } finally {
+      setIsDownloading(false);
+    }
+  }, [isDownloading]);
+
+  const handleTogglePauseDownload = useCallback(async () => {
+    if (jobId === null) return;
+
+    try {
+      if (isPaused) {
+        await frpcDownloader.resumeDownload(jobId);
+      } else {
+        await frpcDownloader.pauseDownload(jobId);
+      }
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      toast.error(`操作失败: ${errorMsg}`, { duration: 3000 });
+    }
+  }, [jobId, isPaused]);
+
+  const handleCancelDownload = useCallback(async () => {
+    if (jobId === null) return;
+
+    try {
+      await frpcDownloader.cancelDownload(jobId);
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      toast.error(`取消下载失败: ${errorMsg}`, { duration: 3000 });
+    }
+  }, [jobId]);
+
   return {
     isDownloading,
+    isPaused,
+    canControlDownload: jobId !== null,
     progress,
     handleRedownloadFrpc,
+    handleCheckFrpcUpdate,
+    handleRollbackFrpc,
+    handleTogglePauseDownload,
+    handleCancelDownload,
   };
 }
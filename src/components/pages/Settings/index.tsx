@@ -73,7 +73,16 @@ export function Settings() {
   const [isDownloadingUpdate, setIsDownloadingUpdate] = useState(false);
   const [downloadProgress, setDownloadProgress] = useState(0);
 
-  const { isDownloading, handleRedownloadFrpc } = useFrpcDownload();
+  const {
+    isDownloading,
+    isPaused: isDownloadPaused,
+    canControlDownload,
+    handleRedownloadFrpc,
+    handleCheckFrpcUpdate,
+    handleRollbackFrpc,
+    handleTogglePauseDownload,
+    handleCancelDownload,
+  } = useFrpcDownload();
 
   const { closeToTrayEnabled, handleToggleCloseToTray } = useCloseBehavior();
 
@@ -288,7 +297,13 @@ export function Settings() {
           currentVersion={currentVersion}
           onCheckUpdate={handleCheckUpdate}
           isDownloading={isDownloading}
+          isDownloadPaused={isDownloadPaused}
+          canControlDownload={canControlDownload}
           onRedownloadFrpc={handleRedownloadFrpc}
+          onCheckFrpcUpdate={handleCheckFrpcUpdate}
+          onRollbackFrpc={handleRollbackFrpc}
+          onTogglePauseDownload={handleTogglePauseDownload}
+          onCancelDownload={handleCancelDownload}
         />
       </div>
 
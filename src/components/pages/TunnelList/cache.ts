@@ -5,4 +5,4 @@ export const tunnelListCache = {
   tunnels: [] as Tunnel[],
 };
 
-export const tunnelProgressCache = new Map<number, TunnelProgress>();
+export const tunnelProgressCache = new Map<string, TunnelProgress>();
@@ -6,6 +6,11 @@ import {
   ContextMenuItem,
   ContextMenuTrigger,
   ContextMenuSeparator,
+  ContextMenuSub,
+  ContextMenuSubTrigger,
+  ContextMenuSubContent,
+  ContextMenuRadioGroup,
+  ContextMenuRadioItem,
 } from "@/components/ui/context-menu";
 import {
   Tooltip,
@@ -16,6 +21,11 @@ import {
 import { deleteTunnel } from "@/services/api";
 import { customTunnelService } from "@/services/customTunnelService";
 import { autoStartTunnelsService } from "@/services/autoStartTunnelsService";
+import { settingsService } from "@/services/settingsService";
+import {
+  frpcVersionService,
+  type InstalledFrpcVersion,
+} from "@/services/frpcVersionService";
 import type { TunnelProgress, UnifiedTunnel } from "../types";
 import { toast } from "sonner";
 
@@ -105,6 +115,21 @@ export function TunnelCard({
   })();
 
   const [autoStartEnabled, setAutoStartEnabled] = useState(false);
+  const [installedVersions, setInstalledVersions] = useState<
+    InstalledFrpcVersion[]
+  >([]);
+  const [pinnedVersion, setPinnedVersion] = useState<string | null>(null);
+  const tunnelKey = `${isApi ? "api" : "custom"}_${tunnel.data.id}`;
+
+  // 打开菜单时刷新，下载新版本后无需重新加载页面
+  const loadFrpcVersions = async () => {
+    const [versions, pinned] = await Promise.all([
+      frpcVersionService.listVersions(),
+      frpcVersionService.getTunnelVersion(tunnelKey),
+    ]);
+    setInstalledVersions(versions);
+    setPinnedVersion(pinned);
+  };
 
   useEffect(() => {
     const loadAutoStartSetting = async () => {
@@ -121,6 +146,28 @@ export function TunnelCard({
     };
 
     loadAutoStartSetting();
+
+    // 其他入口修改自动启动设置后同步状态
+    let unlisten: (() => void) | undefined;
+    let disposed = false;
+    settingsService
+      .onChanged((key) => {
+        if (key === "tunnel_auto_start") {
+          loadAutoStartSetting();
+        }
+      })
+      .then((fn) => {
+        if (disposed) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      });
+
+    return () => {
+      disposed = true;
+      unlisten?.();
+    };
   }, [tunnel, isApi]);
 
   useEffect(() => {
@@ -186,8 +233,31 @@ export function TunnelCard({
     }
   };
 
+  const handleSelectFrpcVersion = async (value: string) => {
+    const version = value === "" ? null : value;
+    try {
+      await frpcVersionService.setTunnelVersion(tunnelKey, version);
+      setPinnedVersion(version);
+      toast.success(
+        version
+          ? `已固定使用 frpc ${version}，下次启动隧道时生效`
+          : "已改为使用默认的 frpc，下次启动隧道时生效",
+      );
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      toast.error(message || "设置 frpc 版本失败");
+      console.error("设置隧道 frpc 版本失败:", error);
+    }
+  };
+
   return (
-    <ContextMenu>
+    <ContextMenu
+      onOpenChange={(open) => {
+        if (open) {
+          loadFrpcVersions();
+        }
+      }}
+    >
       <ContextMenuTrigger asChild>
         <div className="group rounded-lg overflow-hidden transition-all bg-card">
           <div className="w-full bg-muted/20">
@@ -345,6 +415,42 @@ export function TunnelCard({
               </p>
             </TooltipContent>
           </Tooltip>
+          <ContextMenuSub>
+            <ContextMenuSubTrigger className="text-xs">
+              frpc 版本
+            </ContextMenuSubTrigger>
+            <ContextMenuSubContent className="w-36">
+              <ContextMenuRadioGroup
+                value={pinnedVersion ?? ""}
+                onValueChange={handleSelectFrpcVersion}
+              >
+                <ContextMenuRadioItem value="" className="text-xs">
+                  默认
+                </ContextMenuRadioItem>
+                {installedVersions.map((item) => (
+                  <ContextMenuRadioItem
+                    key={item.version}
+                    value={item.version}
+                    className="text-xs"
+                  >
+                    {item.version}
+                  </ContextMenuRadioItem>
+                ))}
+                {pinnedVersion &&
+                  !installedVersions.some(
+                    (item) => item.version === pinnedVersion,
+                  ) && (
+                    <ContextMenuRadioItem
+                      value={pinnedVersion}
+                      className="text-xs"
+                      disabled
+                    >
+                      {pinnedVersion}（未安装）
+                    </ContextMenuRadioItem>
+                  )}
+              </ContextMenuRadioGroup>
+            </ContextMenuSubContent>
+          </ContextMenuSub>
           {onEdit && (
             <ContextMenuItem onClick={() => onEdit(tunnel)} className="text-xs">
               编辑隧道
@@ -1,12 +1,16 @@
-import { useEffect, useRef } from "react";
+import { useCallback, useEffect, useRef } from "react";
+import type { Dispatch, SetStateAction } from "react";
 import { autoStartTunnelsService } from "@/services/autoStartTunnelsService";
 import { getStoredUser } from "@/services/api";
+import { frpcManager, type BatchTunnelResult } from "@/services/frpcManager";
+import { customTunnelService } from "@/services/customTunnelService";
 import type { UnifiedTunnel } from "../types";
 
 interface UseAutoStartTunnelsProps {
   tunnels: UnifiedTunnel[];
   loading: boolean;
   runningTunnels: Set<string>;
+  setRunningTunnels: Dispatch<SetStateAction<Set<string>>>;
   onToggle: (tunnel: UnifiedTunnel, enabled: boolean) => void;
 }
 
@@ -14,6 +18,7 @@ export function useAutoStartTunnels({
   tunnels,
   loading,
   runningTunnels,
+  setRunningTunnels,
   onToggle,
 }: UseAutoStartTunnelsProps) {
   const hasAutoStartedRef = useRef(false);
@@ -33,6 +38,47 @@ export function useAutoStartTunnels({
     onToggleRef.current = onToggle;
   }, [onToggle]);
 
+  const syncStartedTunnels = useCallback(
+    (results: BatchTunnelResult[]) => {
+      const started = results.filter((result) => result.success);
+      if (started.length === 0) {
+        return;
+      }
+      setRunningTunnels((prev) => {
+        const next = new Set(prev);
+        for (const result of started) {
+          next.add(`${result.kind}_${result.tunnel_id}`);
+        }
+        return next;
+      });
+    },
+    [setRunningTunnels],
+  );
+
+  // 后端在启动时已直接启动自动启动隧道，这里同步运行状态
+  useEffect(() => {
+    const setupListener = async () => {
+      const { listen } = await import("@tauri-apps/api/event");
+      return await listen<BatchTunnelResult[]>(
+        "tunnels-auto-started",
+        (event) => {
+          syncStartedTunnels(event.payload);
+        },
+      );
+    };
+
+    let unlistenFn: (() => void) | undefined;
+    setupListener().then((fn) => {
+      unlistenFn = fn;
+    });
+
+    return () => {
+      if (unlistenFn) {
+        unlistenFn();
+      }
+    };
+  }, [syncStartedTunnels]);
+
   useEffect(() => {
     if (hasAutoStartedRef.current) {
       return;
@@ -42,6 +88,44 @@ export function useAutoStartTunnels({
       return;
     }
 
+    // 返回后端启动失败的隧道，后端未负责自动启动时返回 null
+    const waitForLaunchAutoStart = async (): Promise<Set<string> | null> => {
+      // 先开始监听，避免查询状态后、监听前错过完成事件
+      const { listen } = await import("@tauri-apps/api/event");
+      let resolveResults: (results: BatchTunnelResult[]) => void = () => {};
+      const resultsPromise = new Promise<BatchTunnelResult[]>((resolve) => {
+        resolveResults = resolve;
+      });
+      const unlisten = await listen<BatchTunnelResult[]>(
+        "tunnels-auto-started",
+        (event) => {
+          resolveResults(event.payload);
+        },
+      );
+
+      try {
+        const launchState =
+          await autoStartTunnelsService.getLaunchAutoStartState();
+        if (!launchState.enabled) {
+          return null;
+        }
+
+        let results = launchState.results;
+        if (results) {
+          syncStartedTunnels(results);
+        } else {
+          results = await resultsPromise;
+        }
+        return new Set(
+          results
+            .filter((result) => !result.success)
+            .map((result) => `${result.kind}_${result.tunnel_id}`),
+        );
+      } finally {
+        unlisten();
+      }
+    };
+
     const autoStart = async () => {
       try {
         const autoStartList =
@@ -53,30 +137,42 @@ export function useAutoStartTunnels({
 
         const user = getStoredUser();
         const currentTunnels = tunnelsRef.current;
-        const hasApiTunnels = autoStartList.some(([type]) => type === "api");
+        const hasApiTunnels = autoStartList.some(
+          (entry) => entry.kind === "api",
+        );
 
         if (hasApiTunnels && !user?.usertoken) {
           hasAutoStartedRef.current = true;
           return;
         }
 
-        const autoStartSet = new Set<string>();
-        for (const [tunnelType, tunnelId] of autoStartList) {
-          autoStartSet.add(`${tunnelType}_${tunnelId}`);
+        const tunnelsByKey = new Map<string, UnifiedTunnel>();
+        for (const tunnel of currentTunnels) {
+          tunnelsByKey.set(`${tunnel.type}_${tunnel.data.id}`, tunnel);
         }
 
-        await new Promise((resolve) => setTimeout(resolve, 1000));
+        // 后端负责按顺序和延迟启动时，等待其全部完成，只补启动后端未能启动的隧道
+        const failedKeys = await waitForLaunchAutoStart();
+        if (failedKeys === null) {
+          await new Promise((resolve) => setTimeout(resolve, 1000));
+        } else if (failedKeys.size === 0) {
+          hasAutoStartedRef.current = true;
+          return;
+        }
 
         const currentRunningTunnels = runningTunnelsRef.current;
         const toggle = onToggleRef.current;
 
-        for (const tunnel of currentTunnels) {
-          const tunnelKey =
-            tunnel.type === "api"
-              ? `api_${tunnel.data.id}`
-              : `custom_${tunnel.data.id}`;
+        // 按自动启动顺序依次启动
+        for (const entry of autoStartList) {
+          const tunnelKey = `${entry.kind}_${entry.tunnel_id}`;
+          const tunnel = tunnelsByKey.get(tunnelKey);
 
-          if (!autoStartSet.has(tunnelKey)) {
+          if (!tunnel) {
+            continue;
+          }
+
+          if (failedKeys && !failedKeys.has(tunnelKey)) {
             continue;
           }
 
@@ -84,6 +180,17 @@ export function useAutoStartTunnels({
             continue;
           }
 
+          // 仅补启动未运行的隧道（如尚无缓存配置的 API 隧道）
+          const isRunning =
+            tunnel.type === "api"
+              ? await frpcManager.isTunnelRunning(tunnel.data.id)
+              : await customTunnelService.isCustomTunnelRunning(
+                  tunnel.data.id,
+                );
+          if (isRunning) {
+            continue;
+          }
+
           toggle(tunnel, true);
 
           await new Promise((resolve) => setTimeout(resolve, 500));
@@ -97,5 +204,5 @@ export function useAutoStartTunnels({
     };
 
     autoStart();
-  }, [loading, tunnels.length]);
+  }, [loading, tunnels.length, syncStartedTunnels]);
 }
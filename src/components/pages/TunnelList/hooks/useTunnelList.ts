@@ -105,7 +105,7 @@ export function useTunnelList() {
   useEffect(() => {
     const setupAutoRestartListener = async () => {
       const { listen } = await import("@tauri-apps/api/event");
-      const unlisten = await listen<{ tunnel_id: number; timestamp: string }>(
+      const unlisten = await listen<{ tunnel_key: string; timestamp: string }>(
         "tunnel-auto-restarted",
         async () => {
           // 使用ref获取最新的tunnels
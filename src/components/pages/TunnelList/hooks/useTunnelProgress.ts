@@ -21,8 +21,8 @@ export function useTunnelProgress(
     const cached = new Map<string, TunnelProgress>();
     const logs = logStore.getLogs();
     const restored = restoreProgressFromLogs(logs);
-    for (const [tunnelId, progress] of restored) {
-      cached.set(`api_${tunnelId}`, progress);
+    for (const [tunnelKey, progress] of restored) {
+      cached.set(tunnelKey, progress);
     }
     return cached;
   });
@@ -39,11 +39,12 @@ export function useTunnelProgress(
   const processedErrorsRef = useRef<Set<string>>(new Set());
   const playedSoundRef = useRef<Set<string>>(new Set());
   const processedLogsCountRef = useRef<number>(0);
-  const loggedSuccessRef = useRef<Set<number>>(new Set());
+  const loggedSuccessRef = useRef<Set<string>>(new Set());
   const duplicateFixAttemptsRef = useRef<Map<number, number>>(new Map());
 
   const handleDuplicateTunnelError = useCallback(
     async (tunnelId: number, tunnelName: string) => {
+      const tunnelKey = `api_${tunnelId}`;
       const user = getStoredUser();
       if (!user?.usertoken) {
         toast.error("未找到用户令牌，请重新登录");
@@ -93,7 +94,7 @@ export function useTunnelProgress(
         .replace(/\//g, "/");
 
       logStore.addLog({
-        tunnel_id: tunnelId,
+        tunnel_key: tunnelKey,
         message:
           "[I] [ChmlFrpLauncher] 隧道重复启动导致隧道启动失败，自动修复中....",
         timestamp,
@@ -110,7 +111,6 @@ export function useTunnelProgress(
 
         const tunnel = tunnels.find((t) => t.id === tunnelId);
         if (tunnel) {
-          const tunnelKey = `api_${tunnelId}`;
           setTunnelProgress((prev) => {
             const next = new Map(prev);
             const resetProgress = {
@@ -119,7 +119,7 @@ export function useTunnelProgress(
               isSuccess: false,
             };
             next.set(tunnelKey, resetProgress);
-            tunnelProgressCache.set(tunnelId, resetProgress);
+            tunnelProgressCache.set(tunnelKey, resetProgress);
             return next;
           });
 
@@ -144,7 +144,7 @@ export function useTunnelProgress(
             const logs = logStore.getLogs();
             const successLogs = logs.filter(
               (log) =>
-                log.tunnel_id === tunnelId &&
+                log.tunnel_key === tunnelKey &&
                 log.message.includes("映射启动成功"),
             );
 
@@ -160,7 +160,7 @@ export function useTunnelProgress(
 
             const recentErrorLogs = logs.filter(
               (log) =>
-                log.tunnel_id === tunnelId &&
+                log.tunnel_key === tunnelKey &&
                 log.message.includes("启动失败") &&
                 log.message.includes("already exists"),
             );
@@ -172,7 +172,6 @@ export function useTunnelProgress(
                 "因为隧道重复启动导致映射启动失败。系统自动修复失败，请更换外网端口或节点",
                 { duration: 8000 },
               );
-              const tunnelKey = `api_${tunnelId}`;
               setTunnelProgress((prev) => {
                 const current = prev.get(tunnelKey);
                 if (current) {
@@ -182,7 +181,7 @@ export function useTunnelProgress(
                     isError: true,
                     isSuccess: false,
                   };
-                  tunnelProgressCache.set(tunnelId, errorProgress);
+                  tunnelProgressCache.set(tunnelKey, errorProgress);
 
                   if (!playedSoundRef.current.has(tunnelKey)) {
                     playedSoundRef.current.add(tunnelKey);
@@ -208,7 +207,6 @@ export function useTunnelProgress(
       } catch (err) {
         const message = err instanceof Error ? err.message : "自动修复失败";
         toast.error(message, { duration: 5000 });
-        const tunnelKey = `api_${tunnelId}`;
         setTunnelProgress((prev) => {
           const current = prev.get(tunnelKey);
           if (current) {
@@ -217,7 +215,7 @@ export function useTunnelProgress(
               progress: 100,
               isError: true,
             };
-            tunnelProgressCache.set(tunnelId, errorProgress);
+            tunnelProgressCache.set(tunnelKey, errorProgress);
 
             if (!playedSoundRef.current.has(tunnelKey)) {
               playedSoundRef.current.add(tunnelKey);
@@ -243,6 +241,7 @@ export function useTunnelProgress(
 
   const handleTlsError = useCallback(
     async (tunnelId: number) => {
+      const tunnelKey = `api_${tunnelId}`;
       const user = getStoredUser();
       if (!user?.usertoken) {
         toast.error("未找到用户令牌，请重新登录");
@@ -273,7 +272,6 @@ export function useTunnelProgress(
           return;
         }
 
-        const tunnelKey = `api_${tunnelId}`;
         setTunnelProgress((prev) => {
           const next = new Map(prev);
           const resetProgress = {
@@ -282,7 +280,7 @@ export function useTunnelProgress(
             isSuccess: false,
           };
           next.set(tunnelKey, resetProgress);
-          tunnelProgressCache.set(tunnelId, resetProgress);
+          tunnelProgressCache.set(tunnelKey, resetProgress);
           return next;
         });
 
@@ -300,7 +298,7 @@ export function useTunnelProgress(
           const logs = logStore.getLogs();
           const successLogs = logs.filter(
             (log) =>
-              log.tunnel_id === tunnelId &&
+              log.tunnel_key === tunnelKey &&
               log.message.includes("映射启动成功"),
           );
 
@@ -316,7 +314,7 @@ export function useTunnelProgress(
 
           const recentErrorLogs = logs.filter(
             (log) =>
-              log.tunnel_id === tunnelId &&
+              log.tunnel_key === tunnelKey &&
               (log.message.includes("启动失败") ||
                 log.message.includes("请尝试将配置文件中tls_enable")),
           );
@@ -334,7 +332,7 @@ export function useTunnelProgress(
                   isError: true,
                   isSuccess: false,
                 };
-                tunnelProgressCache.set(tunnelId, errorProgress);
+                tunnelProgressCache.set(tunnelKey, errorProgress);
 
                 if (!playedSoundRef.current.has(tunnelKey)) {
                   playedSoundRef.current.add(tunnelKey);
@@ -360,7 +358,6 @@ export function useTunnelProgress(
         toast.error(`自动修复失败，请尝试更换节点`, {
           duration: 5000,
         });
-        const tunnelKey = `api_${tunnelId}`;
         setTunnelProgress((prev) => {
           const current = prev.get(tunnelKey);
           if (current) {
@@ -369,7 +366,7 @@ export function useTunnelProgress(
               progress: 100,
               isError: true,
             };
-            tunnelProgressCache.set(tunnelId, errorProgress);
+            tunnelProgressCache.set(tunnelKey, errorProgress);
 
             if (!playedSoundRef.current.has(tunnelKey)) {
               playedSoundRef.current.add(tunnelKey);
@@ -408,22 +405,21 @@ export function useTunnelProgress(
 
           setTunnelProgress((prev) => {
             const merged = new Map(prev);
-            for (const [tunnelId, progress] of restored) {
-              const tunnelKey = `api_${tunnelId}`;
-              if (!runningSet.has(tunnelId)) {
+            for (const [tunnelKey, progress] of restored) {
+              if (!runningSet.has(tunnelKey)) {
                 merged.set(tunnelKey, {
                   progress: 0,
                   isError: false,
                   isSuccess: false,
                 });
-                tunnelProgressCache.set(tunnelId, {
+                tunnelProgressCache.set(tunnelKey, {
                   progress: 0,
                   isError: false,
                   isSuccess: false,
                 });
               } else {
                 merged.set(tunnelKey, { ...progress, isSuccess: false });
-                tunnelProgressCache.set(tunnelId, {
+                tunnelProgressCache.set(tunnelKey, {
                   ...progress,
                   isSuccess: false,
                 });
@@ -457,8 +453,12 @@ export function useTunnelProgress(
       processedLogsCountRef.current = logs.length;
 
       for (const log of newLogs) {
-        const tunnelId = log.tunnel_id;
-        const tunnelKey = `api_${tunnelId}`;
+        const tunnelKey = log.tunnel_key;
+        // 自定义隧道没有 API 隧道 ID，自动修复只针对 API 隧道
+        const isApiTunnel = tunnelKey.startsWith("api_");
+        const tunnelId = isApiTunnel
+          ? Number(tunnelKey.slice("api_".length))
+          : NaN;
         const message = log.message;
 
         setTunnelProgress((prev) => {
@@ -477,7 +477,7 @@ export function useTunnelProgress(
             newProgress.startTime = Date.now();
             newProgress.progress = 10;
             playedSoundRef.current.delete(tunnelKey);
-            loggedSuccessRef.current.delete(tunnelId);
+            loggedSuccessRef.current.delete(tunnelKey);
             if (timeoutRefs.current.has(tunnelKey)) {
               clearTimeout(timeoutRefs.current.get(tunnelKey)!);
             }
@@ -490,7 +490,7 @@ export function useTunnelProgress(
                     progress: 100,
                     isError: true,
                   };
-                  tunnelProgressCache.set(tunnelId, errorProgress);
+                  tunnelProgressCache.set(tunnelKey, errorProgress);
 
                   if (!playedSoundRef.current.has(tunnelKey)) {
                     playedSoundRef.current.add(tunnelKey);
@@ -533,8 +533,8 @@ export function useTunnelProgress(
             }
 
             const tunnel = tunnels.find((t) => t.id === tunnelId);
-            if (tunnel && !loggedSuccessRef.current.has(tunnelId)) {
-              loggedSuccessRef.current.add(tunnelId);
+            if (tunnel && !loggedSuccessRef.current.has(tunnelKey)) {
+              loggedSuccessRef.current.add(tunnelKey);
 
               const timestamp = new Date()
                 .toLocaleString("zh-CN", {
@@ -557,13 +557,13 @@ export function useTunnelProgress(
                 const link = tunnel.dorp || "";
                 if (link) {
                   logStore.addLog({
-                    tunnel_id: tunnelId,
+                    tunnel_key: tunnelKey,
                     message: `[I] [ChmlFrpLauncher] 隧道"${tunnelName}"启动成功，您可以通过"${link}"访问。`,
                     timestamp,
                   });
                 } else {
                   logStore.addLog({
-                    tunnel_id: tunnelId,
+                    tunnel_key: tunnelKey,
                     message: `[I] [ChmlFrpLauncher] 隧道"${tunnelName}"启动成功。`,
                     timestamp,
                   });
@@ -615,7 +615,7 @@ export function useTunnelProgress(
                 messageText += "。";
 
                 logStore.addLog({
-                  tunnel_id: tunnelId,
+                  tunnel_key: tunnelKey,
                   message: messageText,
                   timestamp,
                 });
@@ -630,7 +630,7 @@ export function useTunnelProgress(
                     ...current,
                     isSuccess: false,
                   };
-                  tunnelProgressCache.set(tunnelId, updated);
+                  tunnelProgressCache.set(tunnelKey, updated);
                   playedSoundRef.current.delete(tunnelKey);
                   return new Map(prev).set(tunnelKey, updated);
                 }
@@ -643,13 +643,13 @@ export function useTunnelProgress(
             message.includes("启动失败") &&
             message.includes("already exists")
           ) {
-            const errorKey = `${tunnelId}-${message}`;
+            const errorKey = `${tunnelKey}-${message}`;
 
             if (processedErrorsRef.current.has(errorKey)) {
               return prev;
             }
 
-            if (!fixingTunnels.has(tunnelId)) {
+            if (isApiTunnel && !fixingTunnels.has(tunnelId)) {
               const match = message.match(/\[([^\]]+)\]/g);
               let tunnelName = "";
 
@@ -686,7 +686,7 @@ export function useTunnelProgress(
               }
             }
           } else if (message.includes("429 Unknown Status")) {
-            const errorKey = `${tunnelId}-429-Unknown-Status`;
+            const errorKey = `${tunnelKey}-429-Unknown-Status`;
 
             if (processedErrorsRef.current.has(errorKey)) {
               return prev;
@@ -712,7 +712,7 @@ export function useTunnelProgress(
             const now = new Date();
             const timestamp = `${now.getHours().toString().padStart(2, "0")}:${now.getMinutes().toString().padStart(2, "0")}:${now.getSeconds().toString().padStart(2, "0")}`;
             logStore.addLog({
-              tunnel_id: tunnelId,
+              tunnel_key: tunnelKey,
               message: errorMessage,
               timestamp,
             });
@@ -736,13 +736,13 @@ export function useTunnelProgress(
             message.includes("请尝试将配置文件中tls_enable") &&
             message.includes("改为tls_enable = true")
           ) {
-            const errorKey = `${tunnelId}-tls-error`;
+            const errorKey = `${tunnelKey}-tls-error`;
 
             if (processedErrorsRef.current.has(errorKey)) {
               return prev;
             }
 
-            if (!fixingTlsTunnels.has(tunnelId)) {
+            if (isApiTunnel && !fixingTlsTunnels.has(tunnelId)) {
               processedErrorsRef.current.add(errorKey);
               setTimeout(
                 () => {
@@ -756,7 +756,7 @@ export function useTunnelProgress(
           }
 
           const updated = new Map(prev).set(tunnelKey, { ...newProgress });
-          tunnelProgressCache.set(tunnelId, { ...newProgress });
+          tunnelProgressCache.set(tunnelKey, { ...newProgress });
           return updated;
         });
       }
@@ -804,7 +804,7 @@ export function useTunnelProgress(
                   isError: true,
                   isSuccess: false,
                 };
-                tunnelProgressCache.set(tunnel.id, errorProgress);
+                tunnelProgressCache.set(tunnelKey, errorProgress);
                 return new Map(prev).set(tunnelKey, errorProgress);
               }
               return prev;
@@ -818,7 +818,7 @@ export function useTunnelProgress(
                   isError: false,
                   isSuccess: false,
                 };
-                tunnelProgressCache.set(tunnel.id, cleared);
+                tunnelProgressCache.set(tunnelKey, cleared);
                 return new Map(prev).set(tunnelKey, cleared);
               }
               return prev;
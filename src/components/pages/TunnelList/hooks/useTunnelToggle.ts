@@ -95,26 +95,22 @@ export function useTunnelToggle({
           message = await customTunnelService.stopCustomTunnel(tunnel.data.id);
         }
 
-        const logTunnelId =
-          tunnel.type === "api" ? tunnel.data.id : tunnel.data.hashed_id;
-        if (typeof logTunnelId === "number" && Number.isFinite(logTunnelId)) {
-          const timestamp = new Date()
-            .toLocaleString("zh-CN", {
-              year: "numeric",
-              month: "2-digit",
-              day: "2-digit",
-              hour: "2-digit",
-              minute: "2-digit",
-              second: "2-digit",
-              hour12: false,
-            })
-            .replace(/\//g, "/");
-          logStore.addLog({
-            tunnel_id: logTunnelId,
-            message: `[I] [ChmlFrpLauncher] 隧道"${tunnelName}"已手动停止。`,
-            timestamp,
-          });
-        }
+        const timestamp = new Date()
+          .toLocaleString("zh-CN", {
+            year: "numeric",
+            month: "2-digit",
+            day: "2-digit",
+            hour: "2-digit",
+            minute: "2-digit",
+            second: "2-digit",
+            hour12: false,
+          })
+          .replace(/\//g, "/");
+        logStore.addLog({
+          tunnel_key: tunnelKey,
+          message: `[I] [ChmlFrpLauncher] 隧道"${tunnelName}"已手动停止。`,
+          timestamp,
+        });
 
         toast.success(message || `隧道 ${tunnelName} 已停止`);
         setRunningTunnels((prev) => {
@@ -77,6 +77,7 @@ export function TunnelList({ user }: TunnelListProps) {
     tunnels,
     loading,
     runningTunnels,
+    setRunningTunnels,
     onToggle: handleToggle,
   });
 
@@ -4,17 +4,17 @@ import { tunnelProgressCache } from "./cache";
 
 export function restoreProgressFromLogs(
   logs: LogMessage[],
-): Map<number, TunnelProgress> {
-  const progressMap = new Map<number, TunnelProgress>();
-  const logsByTunnel = new Map<number, LogMessage[]>();
+): Map<string, TunnelProgress> {
+  const progressMap = new Map<string, TunnelProgress>();
+  const logsByTunnel = new Map<string, LogMessage[]>();
   for (const log of logs) {
-    if (!logsByTunnel.has(log.tunnel_id)) {
-      logsByTunnel.set(log.tunnel_id, []);
+    if (!logsByTunnel.has(log.tunnel_key)) {
+      logsByTunnel.set(log.tunnel_key, []);
     }
-    logsByTunnel.get(log.tunnel_id)!.push(log);
+    logsByTunnel.get(log.tunnel_key)!.push(log);
   }
 
-  for (const [tunnelId, tunnelLogs] of logsByTunnel) {
+  for (const [tunnelKey, tunnelLogs] of logsByTunnel) {
     let progress = 0;
     let isError = false;
     let isSuccess = false;
@@ -42,8 +42,8 @@ export function restoreProgressFromLogs(
     }
 
     if (progress > 0) {
-      progressMap.set(tunnelId, { progress, isError, isSuccess });
-      tunnelProgressCache.set(tunnelId, { progress, isError, isSuccess });
+      progressMap.set(tunnelKey, { progress, isError, isSuccess });
+      tunnelProgressCache.set(tunnelKey, { progress, isError, isSuccess });
     }
   }
 
@@ -1,5 +1,19 @@
 import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { BatchTunnelResult, TunnelKind } from "./frpcManager";
+
+export interface AutoStartEntry {
+  kind: TunnelKind;
+  tunnel_id: string;
+  delay_ms: number;
+  order: number;
+}
+
+// 启动软件时后端自动启动隧道的状态，results 在全部启动完成前为 null
+export interface LaunchAutoStartState {
+  enabled: boolean;
+  results: BatchTunnelResult[] | null;
+}
 
 export class AutoStartTunnelsService {
   private unlisten?: UnlistenFn;
@@ -49,22 +63,69 @@ export class AutoStartTunnelsService {
     }
   }
 
+  /**
+   * 设置自动启动隧道启动前的等待时间
+   * @param tunnelType 隧道类型
+   * @param tunnelId 隧道ID
+   * @param delayMs 等待毫秒数
+   */
+  async setTunnelDelay(
+    tunnelType: TunnelKind,
+    tunnelId: number | string,
+    delayMs: number,
+  ): Promise<void> {
+    try {
+      await invoke("set_tunnel_auto_start_delay", {
+        tunnelType,
+        tunnelId: String(tunnelId),
+        delayMs,
+      });
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      throw new Error(`设置隧道启动延迟失败: ${errorMsg}`);
+    }
+  }
+
+  /**
+   * 设置自动启动隧道的启动顺序
+   * @param tunnels 按启动顺序排列的 [(tunnelType, tunnelId), ...]
+   */
+  async setOrder(tunnels: Array<[TunnelKind, string]>): Promise<void> {
+    try {
+      await invoke("set_auto_start_order", { tunnels });
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      throw new Error(`设置自动启动顺序失败: ${errorMsg}`);
+    }
+  }
+
   /**
    * 获取所有自动启动的隧道列表
-   * @returns 返回 [(tunnelType, tunnelId), ...] 的列表
+   * @returns 按启动顺序排列的自动启动设置
    */
-  async getAutoStartTunnels(): Promise<Array<[string, string]>> {
+  async getAutoStartTunnels(): Promise<AutoStartEntry[]> {
     try {
-      const result = await invoke<Array<[string, string]>>(
-        "get_auto_start_tunnels",
-      );
-      return result;
+      return await invoke<AutoStartEntry[]>("get_auto_start_tunnels");
     } catch (error) {
       console.error("获取自动启动隧道列表失败:", error);
       return [];
     }
   }
 
+  /**
+   * 获取启动软件时后端自动启动隧道的状态
+   */
+  async getLaunchAutoStartState(): Promise<LaunchAutoStartState> {
+    try {
+      return await invoke<LaunchAutoStartState>(
+        "get_launch_auto_start_state",
+      );
+    } catch (error) {
+      console.error("获取启动时自动启动状态失败:", error);
+      return { enabled: false, results: null };
+    }
+  }
+
   /**
    * 监听设置变化事件
    * @param callback 回调函数
@@ -0,0 +1,56 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface DownloadMirror {
+  id: string;
+  name: string;
+  // frpc_info.json 的地址
+  manifest_url: string;
+  // 设置后只从清单中取文件名，从该地址下载
+  binary_base_url: string | null;
+  disabled: boolean;
+}
+
+export interface MirrorHealth {
+  consecutive_failures: number;
+  last_error: string | null;
+  last_success_at: string | null;
+  last_failure_at: string | null;
+  last_latency_ms: number | null;
+}
+
+export interface DownloadMirrorStatus extends DownloadMirror {
+  health: MirrorHealth;
+  // 连续失败的镜像在冷却期内排到最后尝试
+  cooling_down: boolean;
+}
+
+export class DownloadMirrorService {
+  /**
+   * 获取下载镜像列表及其健康状态，按尝试顺序排列
+   */
+  async getMirrors(): Promise<DownloadMirrorStatus[]> {
+    try {
+      return await invoke<DownloadMirrorStatus[]>("get_download_mirrors");
+    } catch (error) {
+      console.error("获取下载镜像失败:", error);
+      return [];
+    }
+  }
+
+  /**
+   * 保存下载镜像列表，下载时按列表顺序尝试
+   * @param mirrors 镜像列表，id 为空时自动生成
+   */
+  async setMirrors(mirrors: DownloadMirror[]): Promise<void> {
+    await invoke("set_download_mirrors", { mirrors });
+  }
+
+  /**
+   * 恢复为默认的下载镜像
+   */
+  async resetMirrors(): Promise<void> {
+    await invoke("reset_download_mirrors");
+  }
+}
+
+export const downloadMirrorService = new DownloadMirrorService();
@@ -5,6 +5,40 @@ export interface DownloadProgress {
   downloaded: number;
   total: number;
   percentage: number;
+  // 当前使用的下载镜像名称
+  mirror: string;
+  job_id: number;
+}
+
+// completed / cancelled / failed 为最终状态
+export type DownloadStatus =
+  | "running"
+  | "paused"
+  | "completed"
+  | "cancelled"
+  | "failed";
+
+export interface DownloadStatusEvent {
+  job_id: number;
+  status: DownloadStatus;
+  error: string | null;
+}
+
+export interface DownloadJobInfo {
+  job_id: number;
+  frpc_path: string;
+  status: DownloadStatus;
+}
+
+// 下载被取消时返回的错误信息
+export const DOWNLOAD_CANCELLED_ERROR = "下载已取消";
+
+export interface FrpcUpdateInfo {
+  // 未安装或无法识别时为 null
+  installed_version: string | null;
+  latest_version: string;
+  update_available: boolean;
+  release_notes: string[];
 }
 
 export class FrpcDownloader {
@@ -23,8 +57,16 @@ export class FrpcDownloader {
     return await invoke<string>("get_download_url");
   }
 
+  /**
+   * 下载 frpc
+   * @param onProgress 下载进度回调
+   * @param version 指定版本时安装到该版本的目录，与默认的 frpc 并存
+   * @param force 有隧道正在使用该 frpc 时仍然替换
+   */
   async downloadFrpc(
     onProgress?: (progress: DownloadProgress) => void,
+    version?: string,
+    force = false,
   ): Promise<string> {
     if (onProgress) {
       this.unlisten = await listen<DownloadProgress>(
@@ -36,7 +78,10 @@ export class FrpcDownloader {
     }
 
     try {
-      const path = await invoke<string>("download_frpc");
+      const path = await invoke<string>("download_frpc", {
+        version: version ?? null,
+        force,
+      });
       return path;
     } finally {
       if (this.unlisten) {
@@ -46,6 +91,94 @@ export class FrpcDownloader {
     }
   }
 
+  /**
+   * 比较已安装的 frpc 与下载源中的最新版本
+   */
+  async checkFrpcUpdate(): Promise<FrpcUpdateInfo> {
+    return await invoke<FrpcUpdateInfo>("check_frpc_update");
+  }
+
+  /**
+   * 升级 frpc 到最新版本，旧版本会保留用于回滚
+   * @param onProgress 下载进度回调
+   * @param force 有隧道正在使用该 frpc 时仍然替换
+   * @returns 升级后的版本号
+   */
+  async upgradeFrpc(
+    onProgress?: (progress: DownloadProgress) => void,
+    force = false,
+  ): Promise<string> {
+    if (onProgress) {
+      this.unlisten = await listen<DownloadProgress>(
+        "download-progress",
+        (event: Event<DownloadProgress>) => {
+          onProgress(event.payload);
+        },
+      );
+    }
+
+    try {
+      return await invoke<string>("upgrade_frpc", { force });
+    } finally {
+      if (this.unlisten) {
+        this.unlisten();
+        this.unlisten = undefined;
+      }
+    }
+  }
+
+  /**
+   * 恢复替换前的 frpc，再次回滚可撤销
+   * @param version 指定版本时回滚该版本目录中的 frpc
+   * @param force 有隧道正在使用该 frpc 时仍然替换
+   */
+  async rollbackFrpc(version?: string, force = false): Promise<string> {
+    return await invoke<string>("rollback_frpc", {
+      version: version ?? null,
+      force,
+    });
+  }
+
+  /**
+   * 获取进行中的下载任务
+   */
+  async listDownloads(): Promise<DownloadJobInfo[]> {
+    return await invoke<DownloadJobInfo[]>("list_downloads");
+  }
+
+  /**
+   * 取消下载任务，已下载的部分会被删除
+   */
+  async cancelDownload(jobId: number): Promise<void> {
+    await invoke("cancel_download", { jobId });
+  }
+
+  /**
+   * 暂停下载任务，恢复后从断点继续
+   */
+  async pauseDownload(jobId: number): Promise<void> {
+    await invoke("pause_download", { jobId });
+  }
+
+  async resumeDownload(jobId: number): Promise<void> {
+    await invoke("resume_download", { jobId });
+  }
+
+  /**
+   * 监听下载任务的状态变化
+   * @param callback 状态变化回调
+   */
+  async onDownloadStatus(
+    callback: (event: DownloadStatusEvent) => void,
+  ): Promise<UnlistenFn> {
+    return await listen<DownloadStatusEvent>(
+      "download-status",
+      (event: Event<DownloadStatusEvent>) => {
+        callback(event.payload);
+      },
+    );
+  }
+
   cleanup() {
     if (this.unlisten) {
       this.unlisten();
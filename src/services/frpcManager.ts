@@ -3,11 +3,54 @@ import { listen, type UnlistenFn, type Event } from "@tauri-apps/api/event";
 import { getNodeUdpSupport, type Tunnel } from "./api";
 
 export interface LogMessage {
-  tunnel_id: number;
+  tunnel_key: string;
+  message: string;
+  timestamp: string;
+}
+
+export type FrpcLogEventKind =
+  | "login_success"
+  | "login_failed"
+  | "connect_failed"
+  | "proxy_started"
+  | "proxy_start_failed"
+  | "reconnecting";
+
+export interface FrpcLogEvent {
+  tunnel_key: string;
+  kind: FrpcLogEventKind;
+  level: "trace" | "debug" | "info" | "warn" | "error" | null;
+  source: string | null;
+  run_id: string | null;
+  proxy_name: string | null;
+  reason: string | null;
   message: string;
   timestamp: string;
 }
 
+export type TunnelStatus =
+  | { state: "starting" }
+  | { state: "logging_in" }
+  | { state: "online" }
+  | { state: "reconnecting" }
+  | { state: "failed"; reason: string }
+  | { state: "stopped" };
+
+export interface FrpcExitInfo {
+  tunnel_key: string;
+  pid: number;
+  code: number | null;
+  signal: number | null;
+  success: boolean;
+  stop_method: "graceful" | "forced" | null;
+  timestamp: string;
+}
+
+export interface RunningTunnel {
+  tunnel_key: string;
+  status: TunnelStatus;
+}
+
 export interface TunnelConfig {
   tunnel_id: number;
   tunnel_name: string;
@@ -24,6 +67,22 @@ export interface TunnelConfig {
   log_level: string;
   force_tls: boolean;
   kcp_optimization: boolean;
+  config_format?: string;
+}
+
+export type TunnelKind = "api" | "custom";
+
+export interface BatchTunnelTarget {
+  kind: TunnelKind;
+  tunnel_id: string;
+  config?: TunnelConfig;
+}
+
+export interface BatchTunnelResult {
+  kind: TunnelKind;
+  tunnel_id: string;
+  success: boolean;
+  message: string;
 }
 
 export class FrpcManager {
@@ -120,14 +179,77 @@ export class FrpcManager {
     }
   }
 
-  async getRunningTunnels(): Promise<number[]> {
+  async getRunningTunnels(): Promise<string[]> {
+    const tunnels = await this.getRunningTunnelStatuses();
+    return tunnels.map((tunnel) => tunnel.tunnel_key);
+  }
+
+  async getRunningTunnelStatuses(): Promise<RunningTunnel[]> {
     try {
-      return await invoke<number[]>("get_running_tunnels");
+      return await invoke<RunningTunnel[]>("get_running_tunnels");
     } catch {
       return [];
     }
   }
 
+  async getTunnelStatus(tunnelKey: string): Promise<TunnelStatus> {
+    try {
+      return await invoke<TunnelStatus>("get_tunnel_status", { tunnelKey });
+    } catch {
+      return { state: "stopped" };
+    }
+  }
+
+  async getTunnelExitInfo(tunnelKey: string): Promise<FrpcExitInfo | null> {
+    try {
+      return await invoke<FrpcExitInfo | null>("get_tunnel_exit_info", {
+        tunnelKey,
+      });
+    } catch {
+      return null;
+    }
+  }
+
+  async startTunnels(
+    targets: BatchTunnelTarget[],
+    concurrency?: number,
+  ): Promise<BatchTunnelResult[]> {
+    return await invoke<BatchTunnelResult[]>("start_tunnels", {
+      targets,
+      concurrency,
+    });
+  }
+
+  async stopTunnels(
+    targets: BatchTunnelTarget[],
+    concurrency?: number,
+  ): Promise<BatchTunnelResult[]> {
+    return await invoke<BatchTunnelResult[]>("stop_tunnels", {
+      targets,
+      concurrency,
+    });
+  }
+
+  async restartTunnels(
+    targets: BatchTunnelTarget[],
+    concurrency?: number,
+  ): Promise<BatchTunnelResult[]> {
+    return await invoke<BatchTunnelResult[]>("restart_tunnels", {
+      targets,
+      concurrency,
+    });
+  }
+
+  // 停止隧道时等待 frpc 自行退出的秒数，仅在 macOS / Linux 上生效，Windows 下总是直接结束进程
+  async getStopGracePeriod(): Promise<number> {
+    return await invoke<number>("get_stop_grace_period");
+  }
+
+  // 为 0 时直接结束进程
+  async setStopGracePeriod(secs: number): Promise<void> {
+    await invoke("set_stop_grace_period", { secs });
+  }
+
   async fixFrpcIniTls(): Promise<string> {
     return await invoke<string>("fix_frpc_ini_tls");
   }
@@ -0,0 +1,51 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface InstalledFrpcVersion {
+  version: string;
+  path: string;
+  // 固定使用该版本的隧道，格式为 api_<id> / custom_<id>
+  tunnels: string[];
+}
+
+export class FrpcVersionService {
+  /**
+   * 获取已安装的 frpc 版本，新版本在前
+   */
+  async listVersions(): Promise<InstalledFrpcVersion[]> {
+    try {
+      return await invoke<InstalledFrpcVersion[]>("list_frpc_versions");
+    } catch (error) {
+      console.error("获取已安装的 frpc 版本失败:", error);
+      return [];
+    }
+  }
+
+  /**
+   * 获取隧道固定使用的 frpc 版本，未固定时返回 null
+   * @param tunnelKey 隧道标识
+   */
+  async getTunnelVersion(tunnelKey: string): Promise<string | null> {
+    try {
+      return await invoke<string | null>("get_tunnel_frpc_version", {
+        tunnelKey,
+      });
+    } catch (error) {
+      console.error("获取隧道 frpc 版本失败:", error);
+      return null;
+    }
+  }
+
+  /**
+   * 固定隧道使用的 frpc 版本
+   * @param tunnelKey 隧道标识
+   * @param version 版本号，为 null 时使用默认的 frpc
+   */
+  async setTunnelVersion(
+    tunnelKey: string,
+    version: string | null,
+  ): Promise<void> {
+    await invoke("set_tunnel_frpc_version", { tunnelKey, version });
+  }
+}
+
+export const frpcVersionService = new FrpcVersionService();
@@ -0,0 +1,79 @@
+import { invoke } from "@tauri-apps/api/core";
+
+export interface ProfileManifestEntry {
+  path: string;
+  size: number;
+  sha256: string;
+}
+
+export interface ProfileManifest {
+  format_version: number;
+  app_version: string;
+  created_at: string;
+  files: ProfileManifestEntry[];
+}
+
+export type ProfileConflictStrategy = "skip" | "overwrite";
+
+export type ProfileItemCategory =
+  | "settings"
+  | "custom_tunnel"
+  | "auto_start"
+  | "guard_rules"
+  | "background";
+
+export type ProfileImportAction = "create" | "overwrite" | "skip";
+
+export interface ProfileImportItem {
+  category: ProfileItemCategory;
+  name: string;
+  action: ProfileImportAction;
+}
+
+export interface ProfileImportReport {
+  manifest: ProfileManifest;
+  dry_run: boolean;
+  items: ProfileImportItem[];
+}
+
+export interface ImportProfileOptions {
+  passphrase?: string;
+  strategy?: ProfileConflictStrategy;
+  dryRun?: boolean;
+}
+
+export class ProfileService {
+  /**
+   * 导出配置备份
+   * @param path 备份文件保存路径
+   * @param passphrase 加密密码，为空时不加密
+   */
+  async exportProfile(
+    path: string,
+    passphrase?: string,
+  ): Promise<ProfileManifest> {
+    return await invoke<ProfileManifest>("export_profile", {
+      path,
+      passphrase: passphrase || null,
+    });
+  }
+
+  /**
+   * 导入配置备份，dryRun 为 true 时只预览将要执行的操作
+   * @param path 备份文件路径
+   * @param options 导入选项
+   */
+  async importProfile(
+    path: string,
+    options: ImportProfileOptions = {},
+  ): Promise<ProfileImportReport> {
+    return await invoke<ProfileImportReport>("import_profile", {
+      path,
+      passphrase: options.passphrase || null,
+      strategy: options.strategy ?? "skip",
+      dryRun: options.dryRun ?? false,
+    });
+  }
+}
+
+export const profileService = new ProfileService();
@@ -0,0 +1,43 @@
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+
+export type SettingsKey =
+  | "launcher"
+  | "tunnel_auto_start"
+  | "custom_tunnels"
+  | "frpc_versions"
+  | "download_mirrors";
+
+export interface SettingsRecoveredEvent {
+  key: SettingsKey;
+  backup_path: string;
+  error: string;
+}
+
+export class SettingsService {
+  /**
+   * 监听设置文件变更
+   * @param callback 回调函数，参数为发生变更的设置名称
+   */
+  async onChanged(callback: (key: SettingsKey) => void): Promise<UnlistenFn> {
+    return await listen<{ key: SettingsKey }>("settings-changed", (event) => {
+      callback(event.payload.key);
+    });
+  }
+
+  /**
+   * 监听损坏设置文件的恢复事件
+   * @param callback 回调函数
+   */
+  async onRecovered(
+    callback: (event: SettingsRecoveredEvent) => void,
+  ): Promise<UnlistenFn> {
+    return await listen<SettingsRecoveredEvent>(
+      "settings-recovered",
+      (event) => {
+        callback(event.payload);
+      },
+    );
+  }
+}
+
+export const settingsService = new SettingsService();